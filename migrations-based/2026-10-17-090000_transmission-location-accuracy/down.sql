@@ -0,0 +1,6 @@
+-- This file should undo anything in `up.sql`
+
+ALTER TABLE r09_transmission_locations DROP COLUMN uncertainty;
+
+ALTER TABLE r09_transmission_locations_raw DROP COLUMN time_offset;
+ALTER TABLE r09_transmission_locations_raw DROP COLUMN accuracy;
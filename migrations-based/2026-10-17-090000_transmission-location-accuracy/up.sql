@@ -0,0 +1,9 @@
+-- Your SQL goes here
+
+-- accuracy in meters of the gps fix the raw location was interpolated from and the time in
+-- milliseconds between the fix and the telegram
+ALTER TABLE r09_transmission_locations_raw ADD COLUMN accuracy DOUBLE PRECISION;
+ALTER TABLE r09_transmission_locations_raw ADD COLUMN time_offset BIGINT;
+
+-- radius in meters around the inferred transmission location the true position is expected in
+ALTER TABLE r09_transmission_locations ADD COLUMN uncertainty DOUBLE PRECISION;
@@ -0,0 +1,7 @@
+-- This file should undo anything in `up.sql`
+
+ALTER TABLE r09_transmission_locations DROP COLUMN last_updated;
+ALTER TABLE r09_transmission_locations DROP COLUMN owner_count;
+ALTER TABLE r09_transmission_locations DROP COLUMN run_count;
+ALTER TABLE r09_transmission_locations DROP COLUMN spread;
+ALTER TABLE r09_transmission_locations DROP COLUMN sample_count;
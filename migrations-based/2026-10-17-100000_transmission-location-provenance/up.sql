@@ -0,0 +1,10 @@
+-- Your SQL goes here
+
+-- how many raw locations from how many trekkie runs and users the transmission location was
+-- inferred from, the standard deviation of their distance from it in meters and when it was
+-- last inferred
+ALTER TABLE r09_transmission_locations ADD COLUMN sample_count INT NOT NULL DEFAULT 0;
+ALTER TABLE r09_transmission_locations ADD COLUMN spread DOUBLE PRECISION;
+ALTER TABLE r09_transmission_locations ADD COLUMN run_count INT NOT NULL DEFAULT 0;
+ALTER TABLE r09_transmission_locations ADD COLUMN owner_count INT NOT NULL DEFAULT 0;
+ALTER TABLE r09_transmission_locations ADD COLUMN last_updated TIMESTAMP;
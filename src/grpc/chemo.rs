@@ -0,0 +1,268 @@
+//!
+//! Ready-made server and client for the **Chemo** service. [`ChemoService`] validates incoming
+//! messages before passing them to a [`ChemoHandler`], [`BufferedChemoClient`] is used by
+//! **data-accumulator** to fan out telegrams and keeps them buffered while the receiving service
+//! is unavailable.
+//!
+
+use super::chemo_client::ChemoClient;
+use super::chemo_server::{Chemo, ChemoServer};
+use super::{GrpcGpsPoint, R09GrpcTelegram, ReturnCode, ServiceError, StreamError};
+use crate::locations::gps::InsertGpsPoint;
+use crate::telegrams::r09::R09SaveTelegram;
+
+use tonic::transport::{Channel, Endpoint};
+use tonic::{Request, Response, Status, Streaming};
+
+use std::collections::VecDeque;
+use std::fmt;
+
+/// Number of messages [`BufferedChemoClient::new`] keeps while the server is unavailable.
+pub const DEFAULT_BUFFER_CAPACITY: usize = 1024;
+
+/// Business logic behind a [`ChemoService`]. The handler only receives messages which passed
+/// validation, errors are returned to the client as they are so it can decide whether to retry.
+#[tonic::async_trait]
+pub trait ChemoHandler: Send + Sync + 'static {
+    /// Processes a validated R09 telegram.
+    async fn handle_r09(&self, telegram: R09SaveTelegram) -> Result<(), ServiceError>;
+
+    /// Processes a validated gps point.
+    async fn handle_gps(&self, point: InsertGpsPoint) -> Result<(), ServiceError>;
+}
+
+/// Implementation of the **Chemo** service which validates incoming messages and answers invalid
+/// ones with [`ServiceError::Invalid`].
+pub struct ChemoService<H> {
+    handler: H,
+}
+
+impl<H: ChemoHandler> ChemoService<H> {
+    /// Creates a service passing valid messages to `handler`.
+    pub fn new(handler: H) -> Self {
+        ChemoService { handler }
+    }
+
+    /// Wraps the service into a [`ChemoServer`] that can be added to a tonic server.
+    pub fn into_server(self) -> ChemoServer<Self> {
+        ChemoServer::new(self)
+    }
+}
+
+impl<H: ChemoHandler> ChemoService<H> {
+    async fn process_r09(&self, telegram: R09GrpcTelegram) -> Result<(), ServiceError> {
+        let telegram = R09SaveTelegram::try_from(telegram)
+            .map_err(|e| ServiceError::Invalid(e.to_string()))?;
+
+        self.handler.handle_r09(telegram).await
+    }
+}
+
+#[tonic::async_trait]
+impl<H: ChemoHandler> Chemo for ChemoService<H> {
+    async fn receive_r09(
+        &self,
+        request: Request<R09GrpcTelegram>,
+    ) -> Result<Response<ReturnCode>, Status> {
+        self.process_r09(request.into_inner()).await?;
+        Ok(Response::new(ReturnCode::ok()))
+    }
+
+    async fn receive_gps(
+        &self,
+        request: Request<GrpcGpsPoint>,
+    ) -> Result<Response<ReturnCode>, Status> {
+        let point = InsertGpsPoint::try_from(request.into_inner())
+            .map_err(|e| ServiceError::Invalid(e.to_string()))?;
+
+        self.handler.handle_gps(point).await?;
+        Ok(Response::new(ReturnCode::ok()))
+    }
+
+    /// Processes the telegrams in order, duplicates are skipped. The stream is aborted at the
+    /// first telegram which is invalid or can't be handled with a [`StreamError`] telling the
+    /// client how many telegrams were processed before.
+    async fn receive_r09_stream(
+        &self,
+        request: Request<Streaming<R09GrpcTelegram>>,
+    ) -> Result<Response<ReturnCode>, Status> {
+        let mut stream = request.into_inner();
+        let mut processed = 0;
+        while let Some(telegram) = stream.message().await? {
+            match self.process_r09(telegram).await {
+                Ok(()) | Err(ServiceError::Duplicate(_)) => processed += 1,
+                Err(error) => return Err(StreamError { processed, error }.into()),
+            }
+        }
+
+        Ok(Response::new(ReturnCode::ok()))
+    }
+}
+
+/// Error returned by [`BufferedChemoClient`].
+#[derive(Debug)]
+pub enum ChemoClientError {
+    /// No connection could be established, the messages stay buffered.
+    Connect(tonic::transport::Error),
+    /// The server is unavailable or failed to process the message, the messages stay buffered and
+    /// the connection is reestablished on the next attempt.
+    Unavailable(ServiceError),
+    /// The server rejected the message, it was dropped from the buffer because sending it again
+    /// won't help.
+    Rejected(ServiceError),
+}
+
+impl fmt::Display for ChemoClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChemoClientError::Connect(e) => write!(f, "cannot connect to chemo: {e}"),
+            ChemoClientError::Unavailable(error) => write!(f, "chemo unavailable: {error}"),
+            ChemoClientError::Rejected(error) => write!(f, "chemo rejected message: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for ChemoClientError {}
+
+/// Message waiting inside the buffer of a [`BufferedChemoClient`].
+#[derive(Clone)]
+enum BufferedMessage {
+    R09(R09GrpcTelegram),
+    Gps(GrpcGpsPoint),
+}
+
+/// Client for the **Chemo** service which connects lazily, reconnects after failures and buffers
+/// messages until they are delivered. If the buffer is full the oldest message is dropped.
+pub struct BufferedChemoClient {
+    endpoint: Endpoint,
+    client: Option<ChemoClient<Channel>>,
+    buffer: VecDeque<BufferedMessage>,
+    capacity: usize,
+    dropped: usize,
+}
+
+impl BufferedChemoClient {
+    /// Creates a client with a buffer of [`DEFAULT_BUFFER_CAPACITY`] messages. No connection is
+    /// established until the first message is sent.
+    pub fn new(endpoint: Endpoint) -> Self {
+        Self::with_capacity(endpoint, DEFAULT_BUFFER_CAPACITY)
+    }
+
+    /// Creates a client which buffers up to `capacity` messages, but at least one.
+    pub fn with_capacity(endpoint: Endpoint, capacity: usize) -> Self {
+        BufferedChemoClient {
+            endpoint,
+            client: None,
+            buffer: VecDeque::new(),
+            capacity: capacity.max(1),
+            dropped: 0,
+        }
+    }
+
+    /// Number of messages waiting to be delivered.
+    pub fn buffered(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Number of messages that were dropped because the buffer was full.
+    pub fn dropped(&self) -> usize {
+        self.dropped
+    }
+
+    /// Buffers the telegram and tries to deliver all buffered messages.
+    pub async fn send_r09(&mut self, telegram: R09GrpcTelegram) -> Result<(), ChemoClientError> {
+        self.push(BufferedMessage::R09(telegram));
+        self.flush().await
+    }
+
+    /// Buffers the gps point and tries to deliver all buffered messages.
+    pub async fn send_gps(&mut self, point: GrpcGpsPoint) -> Result<(), ChemoClientError> {
+        self.push(BufferedMessage::Gps(point));
+        self.flush().await
+    }
+
+    /// Tries to deliver all buffered messages in order. Consecutive telegrams are sent over a
+    /// single `receive_r09_stream` call, gps points one by one. Stops at the first message that
+    /// can't be delivered, messages the server reports as [`ServiceError::Duplicate`] were already
+    /// delivered before.
+    pub async fn flush(&mut self) -> Result<(), ChemoClientError> {
+        while let Some(message) = self.buffer.front() {
+            let mut client = match &self.client {
+                Some(client) => client.clone(),
+                None => {
+                    let channel = self
+                        .endpoint
+                        .connect()
+                        .await
+                        .map_err(ChemoClientError::Connect)?;
+                    self.client.insert(ChemoClient::new(channel)).clone()
+                }
+            };
+
+            let (sent, result) = match message {
+                BufferedMessage::Gps(point) => {
+                    let result = client.receive_gps(point.clone()).await;
+                    (1, result.map_err(StreamError::from))
+                }
+                BufferedMessage::R09(_) => {
+                    let telegrams: Vec<R09GrpcTelegram> = self
+                        .buffer
+                        .iter()
+                        .map_while(|message| match message {
+                            BufferedMessage::R09(telegram) => Some(telegram.clone()),
+                            BufferedMessage::Gps(_) => None,
+                        })
+                        .collect();
+                    let sent = telegrams.len();
+                    let result = client
+                        .receive_r09_stream(tokio_stream::iter(telegrams))
+                        .await;
+                    (sent, result.map_err(StreamError::from))
+                }
+            };
+
+            let result = result.and_then(|response| {
+                response
+                    .into_inner()
+                    .into_result()
+                    .map_err(|error| StreamError {
+                        processed: 0,
+                        error,
+                    })
+            });
+            let StreamError { processed, error } = match result {
+                Ok(()) => {
+                    self.buffer.drain(..sent);
+                    continue;
+                }
+                Err(error) => error,
+            };
+
+            // the message at the front is the one the error belongs to
+            self.buffer.drain(..processed.min(sent));
+            match error {
+                ServiceError::Duplicate(_) => {
+                    self.buffer.pop_front();
+                }
+                error if !error.is_retryable() => {
+                    self.buffer.pop_front();
+                    return Err(ChemoClientError::Rejected(error));
+                }
+                error => {
+                    self.client = None;
+                    return Err(ChemoClientError::Unavailable(error));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn push(&mut self, message: BufferedMessage) {
+        if self.buffer.len() >= self.capacity {
+            self.buffer.pop_front();
+            self.dropped += 1;
+        }
+        self.buffer.push_back(message);
+    }
+}
@@ -0,0 +1,102 @@
+//!
+//! The structs, clients and servers in this module are generated from `proto/telegram.proto`.
+//! [`chemo`][crate::grpc::chemo] and [`waypoint`][crate::grpc::waypoint] contain ready-made
+//! implementations of the services on top of them, [`status`][crate::grpc::status] describes how
+//! they report errors.
+//!
+
+#[cfg(feature = "telegrams")]
+pub mod chemo;
+pub mod status;
+#[cfg(all(test, feature = "telegrams"))]
+mod tests;
+pub mod waypoint;
+
+#[allow(missing_docs, non_camel_case_types)]
+mod proto {
+    tonic::include_proto!("tlms");
+}
+
+pub use proto::*;
+pub use status::{ServiceError, StreamError};
+
+use crate::timestamp::{Timestamp, TimestampRangeError};
+
+use std::fmt;
+
+/// Error returned when a gps point or waypoint doesn't contain a valid position.
+#[derive(Debug, Clone, PartialEq)]
+pub enum GpsValidationError {
+    /// The latitude is not a finite value between -90 and 90 degrees.
+    InvalidLatitude(f64),
+    /// The longitude is not a finite value between -180 and 180 degrees.
+    InvalidLongitude(f64),
+}
+
+impl fmt::Display for GpsValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GpsValidationError::InvalidLatitude(lat) => write!(f, "invalid latitude: {lat}"),
+            GpsValidationError::InvalidLongitude(lon) => write!(f, "invalid longitude: {lon}"),
+        }
+    }
+}
+
+impl std::error::Error for GpsValidationError {}
+
+/// Checks that latitude and longitude describe a position on earth.
+pub(crate) fn validate_position(lat: f64, lon: f64) -> Result<(), GpsValidationError> {
+    if !(-90.0..=90.0).contains(&lat) {
+        return Err(GpsValidationError::InvalidLatitude(lat));
+    }
+    if !(-180.0..=180.0).contains(&lon) {
+        return Err(GpsValidationError::InvalidLongitude(lon));
+    }
+
+    Ok(())
+}
+
+impl GrpcWaypoint {
+    /// Checks that latitude and longitude describe a position on earth.
+    pub fn validate(&self) -> Result<(), GpsValidationError> {
+        validate_position(self.lat, self.lon)
+    }
+
+    /// Time stamp of the waypoint.
+    pub fn get_time(&self) -> Result<Timestamp, TimestampRangeError> {
+        Timestamp::try_from(self.time)
+    }
+}
+
+impl GrpcGpsPoint {
+    /// Checks that latitude and longitude describe a position on earth.
+    pub fn validate(&self) -> Result<(), GpsValidationError> {
+        validate_position(self.lat, self.lon)
+    }
+
+    /// Time stamp of the gps point.
+    pub fn get_time(&self) -> Result<Timestamp, TimestampRangeError> {
+        Timestamp::try_from(self.time)
+    }
+}
+
+impl R09GrpcTelegram {
+    /// Time stamp of the telegram.
+    pub fn get_time(&self) -> Result<Timestamp, TimestampRangeError> {
+        Timestamp::try_from(self.time)
+    }
+}
+
+impl WaypointFilter {
+    /// Creates a filter which lets all waypoints of the given regions pass.
+    pub fn regions(regions: impl IntoIterator<Item = i64>) -> Self {
+        WaypointFilter {
+            regions: regions.into_iter().collect(),
+        }
+    }
+
+    /// Returns true if the waypoint passes the filter. An empty filter lets every waypoint pass.
+    pub fn matches(&self, waypoint: &GrpcWaypoint) -> bool {
+        self.regions.is_empty() || self.regions.contains(&waypoint.region)
+    }
+}
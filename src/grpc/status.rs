@@ -0,0 +1,268 @@
+//!
+//! Structured results of the gRPC services. Services answer successful calls with
+//! [`ReturnCode::ok`] and failed ones with a [`Status`] carrying the [`ReturnCode`] in its
+//! details. Clients turn either of them into a [`ServiceError`] which tells them whether sending
+//! the message again makes sense. Errors aborting a client stream are reported as
+//! [`StreamError`] which also tells how many messages were processed before.
+//!
+
+use super::{ReturnCode, ReturnStatus};
+
+use prost::Message;
+use prost::bytes::Bytes;
+use tonic::{Code, Status};
+
+use std::fmt;
+use std::time::Duration;
+
+/// Error reported by a gRPC service.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ServiceError {
+    /// The message is malformed or contains invalid values.
+    Invalid(String),
+    /// The sender is not allowed to send messages e.g. because of a rejected token.
+    Unauthorized(String),
+    /// The message was already received.
+    Duplicate(String),
+    /// The service can't process messages right now.
+    Unavailable {
+        /// description of the problem
+        message: String,
+        /// time the client should wait before trying again
+        retry_after: Option<Duration>,
+    },
+    /// The service failed to process the message e.g. because of a database failure.
+    Internal(String),
+}
+
+impl ServiceError {
+    /// Returns true if sending the same message again may succeed.
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            ServiceError::Unavailable { .. } | ServiceError::Internal(_)
+        )
+    }
+
+    /// Time the service asked the client to wait before trying again.
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            ServiceError::Unavailable { retry_after, .. } => *retry_after,
+            _ => None,
+        }
+    }
+
+    /// Status sent to the client for this error.
+    pub fn get_status(&self) -> ReturnStatus {
+        match self {
+            ServiceError::Invalid(_) => ReturnStatus::Invalid,
+            ServiceError::Unauthorized(_) => ReturnStatus::Unauthorized,
+            ServiceError::Duplicate(_) => ReturnStatus::Duplicate,
+            ServiceError::Unavailable { .. } => ReturnStatus::Unavailable,
+            ServiceError::Internal(_) => ReturnStatus::Internal,
+        }
+    }
+
+    /// Description of the error.
+    pub fn get_message(&self) -> &str {
+        match self {
+            ServiceError::Invalid(message)
+            | ServiceError::Unauthorized(message)
+            | ServiceError::Duplicate(message)
+            | ServiceError::Unavailable { message, .. }
+            | ServiceError::Internal(message) => message,
+        }
+    }
+
+    /// Closest gRPC code for the error.
+    fn get_code(&self) -> Code {
+        match self {
+            ServiceError::Invalid(_) => Code::InvalidArgument,
+            ServiceError::Unauthorized(_) => Code::PermissionDenied,
+            ServiceError::Duplicate(_) => Code::AlreadyExists,
+            ServiceError::Unavailable { .. } => Code::Unavailable,
+            ServiceError::Internal(_) => Code::Internal,
+        }
+    }
+
+    /// Creates the error for a status, returns `None` for [`ReturnStatus::Ok`].
+    fn from_parts(
+        status: ReturnStatus,
+        message: String,
+        retry_after: Option<Duration>,
+    ) -> Option<Self> {
+        match status {
+            ReturnStatus::Ok => None,
+            ReturnStatus::Invalid => Some(ServiceError::Invalid(message)),
+            ReturnStatus::Unauthorized => Some(ServiceError::Unauthorized(message)),
+            ReturnStatus::Duplicate => Some(ServiceError::Duplicate(message)),
+            ReturnStatus::Unavailable => Some(ServiceError::Unavailable {
+                message,
+                retry_after,
+            }),
+            ReturnStatus::Internal => Some(ServiceError::Internal(message)),
+        }
+    }
+}
+
+impl fmt::Display for ServiceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ServiceError::Invalid(message) => write!(f, "invalid message: {message}"),
+            ServiceError::Unauthorized(message) => write!(f, "unauthorized: {message}"),
+            ServiceError::Duplicate(message) => write!(f, "duplicate message: {message}"),
+            ServiceError::Unavailable {
+                message,
+                retry_after: Some(retry_after),
+            } => write!(
+                f,
+                "service unavailable, retry after {}ms: {message}",
+                retry_after.as_millis()
+            ),
+            ServiceError::Unavailable { message, .. } => {
+                write!(f, "service unavailable: {message}")
+            }
+            ServiceError::Internal(message) => write!(f, "internal error: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for ServiceError {}
+
+/// Error which aborted a client stream.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StreamError {
+    /// number of messages which were processed before the error occurred
+    pub processed: usize,
+    /// error of the first message which wasn't processed
+    pub error: ServiceError,
+}
+
+impl fmt::Display for StreamError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} after {} messages", self.error, self.processed)
+    }
+}
+
+impl std::error::Error for StreamError {}
+
+impl ReturnCode {
+    /// Return code of a successfully processed message.
+    pub fn ok() -> Self {
+        ReturnCode {
+            status: ReturnStatus::Ok as i32,
+            message: None,
+            retry_after_ms: None,
+            processed: None,
+        }
+    }
+
+    /// Turns the return code into a result. Unknown statuses are reported as
+    /// [`ServiceError::Internal`].
+    pub fn into_result(self) -> Result<(), ServiceError> {
+        let message = self.message.unwrap_or_default();
+        let Ok(status) = ReturnStatus::try_from(self.status) else {
+            return Err(ServiceError::Internal(format!(
+                "unknown return status {}: {message}",
+                self.status
+            )));
+        };
+
+        match ServiceError::from_parts(
+            status,
+            message,
+            self.retry_after_ms.map(Duration::from_millis),
+        ) {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+}
+
+impl From<ServiceError> for ReturnCode {
+    fn from(error: ServiceError) -> Self {
+        ReturnCode {
+            status: error.get_status() as i32,
+            retry_after_ms: error
+                .retry_after()
+                .map(|retry_after| u64::try_from(retry_after.as_millis()).unwrap_or(u64::MAX)),
+            message: Some(error.get_message().to_string()),
+            processed: None,
+        }
+    }
+}
+
+impl From<ServiceError> for Status {
+    /// Maps the error onto the closest gRPC code and attaches the [`ReturnCode`] as details.
+    fn from(error: ServiceError) -> Self {
+        let code = error.get_code();
+        let message = error.get_message().to_string();
+        let details = Bytes::from(ReturnCode::from(error).encode_to_vec());
+
+        Status::with_details(code, message, details)
+    }
+}
+
+impl From<StreamError> for Status {
+    /// Same as for [`ServiceError`], the [`ReturnCode`] additionally carries the number of
+    /// processed messages.
+    fn from(error: StreamError) -> Self {
+        let code = error.error.get_code();
+        let message = error.error.get_message().to_string();
+        let details = ReturnCode {
+            processed: Some(u64::try_from(error.processed).unwrap_or(u64::MAX)),
+            ..ReturnCode::from(error.error)
+        };
+
+        Status::with_details(code, message, Bytes::from(details.encode_to_vec()))
+    }
+}
+
+impl From<Status> for ServiceError {
+    /// Uses the [`ReturnCode`] inside the details if there is one, otherwise the gRPC code is
+    /// mapped onto the closest error. Transport errors like timeouts are reported as
+    /// [`ServiceError::Unavailable`].
+    fn from(status: Status) -> Self {
+        if let Ok(code) = ReturnCode::decode(status.details())
+            && let Err(error) = code.into_result()
+        {
+            return error;
+        }
+
+        let message = status.message().to_string();
+        match status.code() {
+            Code::InvalidArgument | Code::OutOfRange | Code::FailedPrecondition => {
+                ServiceError::Invalid(message)
+            }
+            Code::Unauthenticated | Code::PermissionDenied => ServiceError::Unauthorized(message),
+            Code::AlreadyExists => ServiceError::Duplicate(message),
+            Code::Unavailable
+            | Code::DeadlineExceeded
+            | Code::ResourceExhausted
+            | Code::Aborted
+            | Code::Cancelled => ServiceError::Unavailable {
+                message,
+                retry_after: None,
+            },
+            _ => ServiceError::Internal(message),
+        }
+    }
+}
+
+impl From<Status> for StreamError {
+    /// Takes the number of processed messages from the [`ReturnCode`] inside the details. If
+    /// there is none, e.g. because the connection broke, no message is assumed to be processed.
+    fn from(status: Status) -> Self {
+        let processed = ReturnCode::decode(status.details())
+            .ok()
+            .and_then(|code| code.processed)
+            .map_or(0, |processed| {
+                usize::try_from(processed).unwrap_or(usize::MAX)
+            });
+
+        StreamError {
+            processed,
+            error: ServiceError::from(status),
+        }
+    }
+}
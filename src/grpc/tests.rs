@@ -0,0 +1,479 @@
+use super::chemo::{BufferedChemoClient, ChemoClientError, ChemoHandler, ChemoService};
+use super::chemo_client::ChemoClient;
+use super::receive_waypoint_client::ReceiveWaypointClient;
+use super::waypoint::{WaypointClient, WaypointHub, WaypointService};
+use super::{
+    GrpcGpsPoint, GrpcWaypoint, R09GrpcTelegram, ReturnCode, ServiceError, StreamError,
+    WaypointFilter,
+};
+use crate::locations::gps::InsertGpsPoint;
+use crate::telegrams::r09::{R09SaveTelegram, R09Type};
+
+use tokio::net::TcpListener;
+use tokio_stream::StreamExt;
+use tokio_stream::wrappers::TcpListenerStream;
+use tonic::transport::{Endpoint, Server};
+use tonic::{Code, Status};
+
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+#[derive(Clone, Default)]
+struct Recorder {
+    telegrams: Arc<Mutex<Vec<R09SaveTelegram>>>,
+    points: Arc<Mutex<Vec<InsertGpsPoint>>>,
+    error: Arc<Mutex<Option<ServiceError>>>,
+    duplicates: Arc<Mutex<Vec<i32>>>,
+}
+
+#[tonic::async_trait]
+impl ChemoHandler for Recorder {
+    async fn handle_r09(&self, telegram: R09SaveTelegram) -> Result<(), ServiceError> {
+        if let Some(error) = self.error.lock().unwrap().clone() {
+            return Err(error);
+        }
+        if self
+            .duplicates
+            .lock()
+            .unwrap()
+            .contains(&telegram.reporting_point)
+        {
+            return Err(ServiceError::Duplicate("seen before".to_string()));
+        }
+        self.telegrams.lock().unwrap().push(telegram);
+        Ok(())
+    }
+
+    async fn handle_gps(&self, point: InsertGpsPoint) -> Result<(), ServiceError> {
+        self.points.lock().unwrap().push(point);
+        Ok(())
+    }
+}
+
+async fn serve(listener: TcpListener, recorder: Recorder) {
+    tokio::spawn(
+        Server::builder()
+            .add_service(ChemoService::new(recorder).into_server())
+            .serve_with_incoming(TcpListenerStream::new(listener)),
+    );
+}
+
+fn endpoint(addr: SocketAddr) -> Endpoint {
+    Endpoint::from_shared(format!("http://{addr}")).expect("invalid endpoint!")
+}
+
+fn telegram(reporting_point: i32) -> R09GrpcTelegram {
+    R09GrpcTelegram {
+        time: 1_681_000_000_123,
+        station: "53e643d7-c300-4de7-ab48-540d08a0cbc6".to_string(),
+        region: 1,
+        r09_type: R09Type::R16 as i32,
+        delay: Some(-2),
+        reporting_point,
+        junction: reporting_point >> 4,
+        direction: 0,
+        request_status: 1,
+        priority: Some(0),
+        direction_request: None,
+        line: Some(85),
+        run_number: Some(12),
+        destination_number: Some(304),
+        train_length: Some(2),
+        vehicle_number: Some(2217),
+        operator: None,
+    }
+}
+
+fn gps_point(lat: f64) -> GrpcGpsPoint {
+    GrpcGpsPoint {
+        time: 1_681_000_000_123,
+        id: 1,
+        region: 1,
+        lat,
+        lon: 13.73,
+        line: 85,
+        run: 12,
+        elevation: None,
+        accuracy: Some(4.0),
+        vertical_accuracy: None,
+        bearing: None,
+        speed: None,
+        trekkie_run: "53e643d7-c300-4de7-ab48-540d08a0cbc6".to_string(),
+    }
+}
+
+#[tokio::test]
+async fn test_chemo_client_delivers() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let recorder = Recorder::default();
+    serve(listener, recorder.clone()).await;
+
+    let mut client = BufferedChemoClient::new(endpoint(addr));
+    client
+        .send_r09(telegram(0x1a2b))
+        .await
+        .expect("cannot send telegram!");
+    client
+        .send_gps(gps_point(51.05))
+        .await
+        .expect("cannot send gps point!");
+
+    assert_eq!(client.buffered(), 0);
+    let telegrams = recorder.telegrams.lock().unwrap();
+    assert_eq!(telegrams.len(), 1);
+    assert_eq!(telegrams[0].reporting_point, 0x1a2b);
+    assert_eq!(recorder.points.lock().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_chemo_service_validates() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let recorder = Recorder::default();
+    serve(listener, recorder.clone()).await;
+
+    let mut client = BufferedChemoClient::new(endpoint(addr));
+
+    let mut invalid = telegram(0x1a2b);
+    invalid.station = "not a uuid".to_string();
+    match client.send_r09(invalid).await {
+        Err(ChemoClientError::Rejected(ServiceError::Invalid(_))) => {}
+        other => panic!("expected rejection, got {other:?}"),
+    }
+
+    match client.send_gps(gps_point(f64::NAN)).await {
+        Err(ChemoClientError::Rejected(ServiceError::Invalid(_))) => {}
+        other => panic!("expected rejection, got {other:?}"),
+    }
+
+    let mut invalid = gps_point(51.05);
+    invalid.trekkie_run = "not a uuid".to_string();
+    match client.send_gps(invalid).await {
+        Err(ChemoClientError::Rejected(ServiceError::Invalid(_))) => {}
+        other => panic!("expected rejection, got {other:?}"),
+    }
+
+    let mut invalid = gps_point(51.05);
+    invalid.id = u64::MAX;
+    match client.send_gps(invalid).await {
+        Err(ChemoClientError::Rejected(ServiceError::Invalid(_))) => {}
+        other => panic!("expected rejection, got {other:?}"),
+    }
+
+    // rejected messages are not retried
+    assert_eq!(client.buffered(), 0);
+    assert!(recorder.telegrams.lock().unwrap().is_empty());
+    assert!(recorder.points.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_chemo_client_buffers_until_reachable() {
+    // reserve a free port and release it again so nobody is listening
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    drop(listener);
+
+    let mut client = BufferedChemoClient::with_capacity(endpoint(addr), 2);
+    for reporting_point in [1, 2, 3] {
+        assert!(matches!(
+            client.send_r09(telegram(reporting_point)).await,
+            Err(ChemoClientError::Connect(_))
+        ));
+    }
+    assert_eq!(client.buffered(), 2);
+    assert_eq!(client.dropped(), 1);
+
+    let listener = TcpListener::bind(addr).await.unwrap();
+    let recorder = Recorder::default();
+    serve(listener, recorder.clone()).await;
+
+    client.flush().await.expect("cannot flush buffer!");
+    assert_eq!(client.buffered(), 0);
+
+    let delivered: Vec<i32> = recorder
+        .telegrams
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|telegram| telegram.reporting_point)
+        .collect();
+    assert_eq!(delivered, vec![2, 3]);
+}
+
+#[tokio::test]
+async fn test_chemo_r09_stream() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let recorder = Recorder::default();
+    serve(listener, recorder.clone()).await;
+
+    let mut client = ChemoClient::connect(endpoint(addr))
+        .await
+        .expect("cannot connect!");
+    client
+        .receive_r09_stream(tokio_stream::iter(vec![telegram(1), telegram(2)]))
+        .await
+        .expect("cannot send stream!");
+
+    // duplicates are skipped, the stream is aborted at the invalid telegram
+    recorder.duplicates.lock().unwrap().push(3);
+    let mut invalid = telegram(4);
+    invalid.r09_type = 15;
+    let status = client
+        .receive_r09_stream(tokio_stream::iter(vec![
+            telegram(3),
+            telegram(6),
+            invalid,
+            telegram(5),
+        ]))
+        .await
+        .expect_err("invalid telegram accepted!");
+    assert_eq!(status.code(), Code::InvalidArgument);
+    let error = StreamError::from(status);
+    assert_eq!(error.processed, 2);
+    assert!(matches!(error.error, ServiceError::Invalid(_)));
+
+    let delivered: Vec<i32> = recorder
+        .telegrams
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|telegram| telegram.reporting_point)
+        .collect();
+    assert_eq!(delivered, vec![1, 2, 6]);
+}
+
+#[tokio::test]
+async fn test_chemo_client_streams_telegrams() {
+    // reserve a free port and release it again so nobody is listening
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    drop(listener);
+
+    let mut client = BufferedChemoClient::new(endpoint(addr));
+    let mut invalid = telegram(2);
+    invalid.r09_type = 15;
+    for telegram in [telegram(1), invalid, telegram(3)] {
+        assert!(matches!(
+            client.send_r09(telegram).await,
+            Err(ChemoClientError::Connect(_))
+        ));
+    }
+    assert!(matches!(
+        client.send_gps(gps_point(51.05)).await,
+        Err(ChemoClientError::Connect(_))
+    ));
+
+    let listener = TcpListener::bind(addr).await.unwrap();
+    let recorder = Recorder::default();
+    serve(listener, recorder.clone()).await;
+
+    // the telegrams in front of the invalid one were delivered within the same stream
+    match client.flush().await {
+        Err(ChemoClientError::Rejected(ServiceError::Invalid(_))) => {}
+        other => panic!("expected rejection, got {other:?}"),
+    }
+    assert_eq!(client.buffered(), 2);
+
+    client.flush().await.expect("cannot flush buffer!");
+    assert_eq!(client.buffered(), 0);
+
+    let delivered: Vec<i32> = recorder
+        .telegrams
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|telegram| telegram.reporting_point)
+        .collect();
+    assert_eq!(delivered, vec![1, 3]);
+    assert_eq!(recorder.points.lock().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_chemo_client_retries_on_handler_error() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let recorder = Recorder::default();
+    serve(listener, recorder.clone()).await;
+
+    let mut client = BufferedChemoClient::new(endpoint(addr));
+
+    *recorder.error.lock().unwrap() = Some(ServiceError::Unauthorized("revoked".to_string()));
+    match client.send_r09(telegram(1)).await {
+        Err(ChemoClientError::Rejected(ServiceError::Unauthorized(message))) => {
+            assert_eq!(message, "revoked")
+        }
+        other => panic!("expected rejection, got {other:?}"),
+    }
+    assert_eq!(client.buffered(), 0);
+
+    *recorder.error.lock().unwrap() = Some(ServiceError::Unavailable {
+        message: "database down".to_string(),
+        retry_after: Some(Duration::from_secs(5)),
+    });
+    match client.send_r09(telegram(2)).await {
+        Err(ChemoClientError::Unavailable(error)) => {
+            assert!(error.is_retryable());
+            assert_eq!(error.retry_after(), Some(Duration::from_secs(5)));
+        }
+        other => panic!("expected unavailable, got {other:?}"),
+    }
+    assert_eq!(client.buffered(), 1);
+
+    *recorder.error.lock().unwrap() = None;
+    client.flush().await.expect("cannot flush buffer!");
+    assert_eq!(recorder.telegrams.lock().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_chemo_client_skips_duplicates() {
+    // reserve a free port and release it again so nobody is listening
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    drop(listener);
+
+    let mut client = BufferedChemoClient::new(endpoint(addr));
+    for reporting_point in [1, 2] {
+        assert!(matches!(
+            client.send_r09(telegram(reporting_point)).await,
+            Err(ChemoClientError::Connect(_))
+        ));
+    }
+
+    let listener = TcpListener::bind(addr).await.unwrap();
+    let recorder = Recorder::default();
+    recorder.duplicates.lock().unwrap().push(1);
+    serve(listener, recorder.clone()).await;
+
+    // the server already has the first telegram, the second one is delivered anyway
+    client.flush().await.expect("cannot flush buffer!");
+    assert_eq!(client.buffered(), 0);
+
+    let delivered: Vec<i32> = recorder
+        .telegrams
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|telegram| telegram.reporting_point)
+        .collect();
+    assert_eq!(delivered, vec![2]);
+}
+
+#[test]
+fn test_service_error_status() {
+    let error = ServiceError::Unavailable {
+        message: "database down".to_string(),
+        retry_after: Some(Duration::from_millis(1500)),
+    };
+    let status = Status::from(error.clone());
+    assert_eq!(status.code(), Code::Unavailable);
+    assert_eq!(ServiceError::from(status), error);
+
+    // statuses without details are mapped by their code
+    assert_eq!(
+        ServiceError::from(Status::permission_denied("invalid token")),
+        ServiceError::Unauthorized("invalid token".to_string())
+    );
+    assert!(!ServiceError::from(Status::already_exists("")).is_retryable());
+    assert!(ServiceError::from(Status::deadline_exceeded("")).is_retryable());
+
+    assert_eq!(ReturnCode::ok().into_result(), Ok(()));
+    let code = ReturnCode {
+        status: 42,
+        message: None,
+        retry_after_ms: None,
+        processed: None,
+    };
+    assert!(matches!(code.into_result(), Err(ServiceError::Internal(_))));
+}
+
+fn waypoint(id: u64, region: i64) -> GrpcWaypoint {
+    GrpcWaypoint {
+        id,
+        source: 1,
+        time: 1_681_000_000_123,
+        region,
+        lat: 51.05,
+        lon: 13.73,
+        line: 85,
+        run: 12,
+        delayed: None,
+        r09_reporting_point: None,
+        r09_destination_number: None,
+    }
+}
+
+#[tokio::test]
+async fn test_subscribe_waypoints() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let hub = WaypointHub::default();
+    tokio::spawn(
+        Server::builder()
+            .add_service(WaypointService::new(hub.clone()).into_server())
+            .serve_with_incoming(TcpListenerStream::new(listener)),
+    );
+
+    let mut client = WaypointClient::connect(endpoint(addr))
+        .await
+        .expect("cannot connect!");
+    let mut subscription = client
+        .subscribe(WaypointFilter::regions([1]))
+        .await
+        .expect("cannot subscribe!");
+    let mut everything = hub.subscribe(WaypointFilter::default());
+    assert_eq!(hub.subscribers(), 2);
+
+    client
+        .send_waypoints([waypoint(1, 1), waypoint(2, 2), waypoint(3, 1)])
+        .await
+        .expect("cannot send stream!");
+
+    // the stream is aborted at the invalid waypoint
+    let mut invalid = waypoint(5, 1);
+    invalid.lat = 91.0;
+    let error = client
+        .send_waypoints([waypoint(4, 1), invalid, waypoint(6, 1)])
+        .await
+        .expect_err("invalid waypoint accepted!");
+    assert_eq!(error.processed, 1);
+    assert!(matches!(error.error, ServiceError::Invalid(_)));
+
+    let mut raw = ReceiveWaypointClient::connect(endpoint(addr))
+        .await
+        .expect("cannot connect!");
+    let mut invalid = waypoint(7, 1);
+    invalid.lat = 91.0;
+    let status = raw
+        .receive_waypoint(invalid)
+        .await
+        .expect_err("invalid waypoint accepted!");
+    assert_eq!(status.code(), Code::InvalidArgument);
+    raw.receive_waypoint(waypoint(8, 1))
+        .await
+        .expect("cannot send waypoint!");
+
+    let mut received = Vec::new();
+    for _ in 0..4 {
+        let waypoint = subscription
+            .next()
+            .await
+            .expect("subscription ended!")
+            .expect("subscription failed!");
+        received.push(waypoint.id);
+    }
+    assert_eq!(received, vec![1, 3, 4, 8]);
+
+    let mut received = Vec::new();
+    for _ in 0..5 {
+        let waypoint = everything
+            .next()
+            .await
+            .expect("subscription ended!")
+            .expect("subscription failed!");
+        received.push(waypoint.id);
+    }
+    assert_eq!(received, vec![1, 2, 3, 4, 8]);
+}
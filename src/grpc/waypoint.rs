@@ -0,0 +1,189 @@
+//!
+//! Live distribution of waypoints. [`WaypointHub`] fans out published waypoints to any number of
+//! subscribers, [`WaypointService`] implements the **ReceiveWaypoint** service on top of it so
+//! live-map consumers can subscribe to the regions they are interested in. [`WaypointClient`]
+//! sends waypoints to the service and subscribes to them.
+//!
+
+use super::receive_waypoint_client::ReceiveWaypointClient;
+use super::receive_waypoint_server::{ReceiveWaypoint, ReceiveWaypointServer};
+use super::{GrpcWaypoint, ReturnCode, ServiceError, StreamError, WaypointFilter};
+#[cfg(feature = "locations")]
+use crate::locations::waypoint::Waypoint;
+
+use tokio::sync::broadcast;
+use tokio_stream::wrappers::BroadcastStream;
+use tokio_stream::{Stream, StreamExt};
+use tonic::transport::{Channel, Endpoint};
+use tonic::{Request, Response, Status, Streaming};
+
+use std::pin::Pin;
+
+/// Number of waypoints [`WaypointHub::default`] keeps for subscribers which are lagging behind.
+pub const DEFAULT_HUB_CAPACITY: usize = 1024;
+
+/// Stream of waypoints returned by [`WaypointHub::subscribe`].
+pub type WaypointStream = Pin<Box<dyn Stream<Item = Result<GrpcWaypoint, Status>> + Send>>;
+
+/// Stream of waypoints returned by [`WaypointClient::subscribe`].
+#[cfg(feature = "locations")]
+pub type WaypointSubscription = Pin<Box<dyn Stream<Item = Result<Waypoint, ServiceError>> + Send>>;
+
+/// Broadcasts waypoints to all subscribers. Cloned hubs share their subscribers.
+#[derive(Clone)]
+pub struct WaypointHub {
+    sender: broadcast::Sender<GrpcWaypoint>,
+}
+
+impl WaypointHub {
+    /// Creates a hub which keeps up to `capacity` waypoints for subscribers which are lagging
+    /// behind. Subscribers which fall further behind skip the oldest waypoints.
+    pub fn new(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity.max(1));
+        WaypointHub { sender }
+    }
+
+    /// Sends the waypoint to all subscribers and returns how many there are.
+    pub fn publish(&self, waypoint: GrpcWaypoint) -> usize {
+        self.sender.send(waypoint).unwrap_or(0)
+    }
+
+    /// Returns a stream of all waypoints published from now on which pass the filter.
+    pub fn subscribe(&self, filter: WaypointFilter) -> WaypointStream {
+        let stream =
+            BroadcastStream::new(self.sender.subscribe()).filter_map(
+                move |waypoint| match waypoint {
+                    Ok(waypoint) if filter.matches(&waypoint) => Some(Ok(waypoint)),
+                    _ => None,
+                },
+            );
+
+        Box::pin(stream)
+    }
+
+    /// Number of active subscribers.
+    pub fn subscribers(&self) -> usize {
+        self.sender.receiver_count()
+    }
+}
+
+impl Default for WaypointHub {
+    fn default() -> Self {
+        Self::new(DEFAULT_HUB_CAPACITY)
+    }
+}
+
+/// Implementation of the **ReceiveWaypoint** service which validates incoming waypoints and
+/// publishes them into a [`WaypointHub`].
+pub struct WaypointService {
+    hub: WaypointHub,
+}
+
+impl WaypointService {
+    /// Creates a service publishing into `hub`.
+    pub fn new(hub: WaypointHub) -> Self {
+        WaypointService { hub }
+    }
+
+    /// Wraps the service into a [`ReceiveWaypointServer`] that can be added to a tonic server.
+    pub fn into_server(self) -> ReceiveWaypointServer<Self> {
+        ReceiveWaypointServer::new(self)
+    }
+
+    fn process_waypoint(&self, waypoint: GrpcWaypoint) -> Result<(), ServiceError> {
+        waypoint
+            .validate()
+            .map_err(|e| ServiceError::Invalid(e.to_string()))?;
+
+        self.hub.publish(waypoint);
+        Ok(())
+    }
+}
+
+#[tonic::async_trait]
+impl ReceiveWaypoint for WaypointService {
+    async fn receive_waypoint(
+        &self,
+        request: Request<GrpcWaypoint>,
+    ) -> Result<Response<ReturnCode>, Status> {
+        self.process_waypoint(request.into_inner())?;
+        Ok(Response::new(ReturnCode::ok()))
+    }
+
+    /// Publishes the waypoints in order, the stream is aborted at the first invalid waypoint with
+    /// a [`StreamError`] telling the client how many waypoints were published before.
+    async fn receive_waypoint_stream(
+        &self,
+        request: Request<Streaming<GrpcWaypoint>>,
+    ) -> Result<Response<ReturnCode>, Status> {
+        let mut stream = request.into_inner();
+        let mut processed = 0;
+        while let Some(waypoint) = stream.message().await? {
+            self.process_waypoint(waypoint)
+                .map_err(|error| StreamError { processed, error })?;
+            processed += 1;
+        }
+
+        Ok(Response::new(ReturnCode::ok()))
+    }
+
+    type subscribe_waypointsStream = WaypointStream;
+
+    async fn subscribe_waypoints(
+        &self,
+        request: Request<WaypointFilter>,
+    ) -> Result<Response<Self::subscribe_waypointsStream>, Status> {
+        Ok(Response::new(self.hub.subscribe(request.into_inner())))
+    }
+}
+
+/// Client for the **ReceiveWaypoint** service.
+#[derive(Clone)]
+pub struct WaypointClient {
+    client: ReceiveWaypointClient<Channel>,
+}
+
+impl WaypointClient {
+    /// Connects to the service at `endpoint`.
+    pub async fn connect(endpoint: Endpoint) -> Result<Self, tonic::transport::Error> {
+        let channel = endpoint.connect().await?;
+        Ok(WaypointClient {
+            client: ReceiveWaypointClient::new(channel),
+        })
+    }
+
+    /// Sends the waypoints in order over a single `receive_waypoint_stream` call.
+    pub async fn send_waypoints(
+        &mut self,
+        waypoints: impl IntoIterator<Item = GrpcWaypoint>,
+    ) -> Result<(), StreamError> {
+        let waypoints: Vec<GrpcWaypoint> = waypoints.into_iter().collect();
+        let response = self
+            .client
+            .receive_waypoint_stream(tokio_stream::iter(waypoints))
+            .await?;
+
+        response
+            .into_inner()
+            .into_result()
+            .map_err(|error| StreamError {
+                processed: 0,
+                error,
+            })
+    }
+
+    /// Subscribes to all waypoints passing `filter`. Waypoints which can't be converted into a
+    /// [`Waypoint`] are reported as [`ServiceError::Invalid`] without ending the stream.
+    #[cfg(feature = "locations")]
+    pub async fn subscribe(
+        &mut self,
+        filter: WaypointFilter,
+    ) -> Result<WaypointSubscription, ServiceError> {
+        let stream = self.client.subscribe_waypoints(filter).await?.into_inner();
+        let stream = stream.map(|waypoint| {
+            Waypoint::try_from(waypoint?).map_err(|e| ServiceError::Invalid(e.to_string()))
+        });
+
+        Ok(Box::pin(stream))
+    }
+}
@@ -63,6 +63,12 @@ pub mod time_serializer {
     }
 }
 
+///
+/// Shared representation of points in time and conversions between the formats used by the
+/// database, the JSON APIs and the gRPC messages.
+///
+pub mod timestamp;
+
 ///
 /// VDV 420 Telegram Definitions
 ///
@@ -123,8 +129,4 @@ pub mod statistics;
 /// between services.
 ///
 #[cfg(feature = "grpc")]
-#[allow(missing_docs)]
-pub mod grpc {
-    use tonic;
-    tonic::include_proto!("tlms");
-}
+pub mod grpc;
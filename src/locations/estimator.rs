@@ -0,0 +1,369 @@
+//!
+//! Strategies to estimate the position of a transmission location from the raw positions measured
+//! during trekkie runs. The raw positions contain outliers e.g. from a trekkie run with bad gps
+//! reception, every [`LocationEstimator`] deals with them differently.
+//!
+//! | Estimator           | Outliers are                                                      |
+//! |---------------------|-------------------------------------------------------------------|
+//! | [`MeanFilter`]      | further away than a fixed radius from the mean of all points      |
+//! | [`GeometricMedian`] | further away than a fixed radius from the geometric median        |
+//! | [`SigmaClipping`]   | iteratively removed if their distance is unusually large          |
+//! | [`Dbscan`]          | not part of the biggest cluster of densely packed points          |
+//!
+
+use super::{
+    DEFAULT_INTERPOLATION_DISTANCE, DistanceFrom, MEAN_EARTH_RADIUS, TransmissionLocaionError,
+};
+
+/// Position returned by a [`LocationEstimator`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Estimate {
+    /// estimated `(latitude, longitude)` of the transmission location
+    pub position: (f64, f64),
+    /// indices of the points the position was estimated from in ascending order, every other
+    /// point was considered an outlier
+    pub inliers: Vec<usize>,
+}
+
+/// Estimates the position of a transmission location from raw positions while ignoring outliers.
+pub trait LocationEstimator {
+    /// Returns the estimated position of the transmission location together with the points it
+    /// was estimated from.
+    fn estimate(&self, points: &[(f64, f64)]) -> Result<Estimate, TransmissionLocaionError>;
+}
+
+/// Averages all points within `radius` meters of the mean of all points. This is the outlier filter
+/// [`InsertTransmissionLocation::try_from_raw`][super::InsertTransmissionLocation::try_from_raw]
+/// uses, it is skewed badly by outliers far away.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MeanFilter {
+    /// maximum distance in meters from the mean
+    pub radius: f64,
+}
+
+impl Default for MeanFilter {
+    fn default() -> Self {
+        MeanFilter {
+            radius: DEFAULT_INTERPOLATION_DISTANCE,
+        }
+    }
+}
+
+impl MeanFilter {
+    /// Returns the indices of the points closer than `radius` meters to the mean of all points.
+    pub fn inliers(&self, points: &[(f64, f64)]) -> Vec<usize> {
+        if points.is_empty() {
+            return Vec::new();
+        }
+
+        let center = mean(points);
+        (0..points.len())
+            .filter(|index| points[*index].distance_from(center) < self.radius)
+            .collect()
+    }
+}
+
+impl LocationEstimator for MeanFilter {
+    fn estimate(&self, points: &[(f64, f64)]) -> Result<Estimate, TransmissionLocaionError> {
+        if points.is_empty() {
+            return Err(TransmissionLocaionError::EmptyInput);
+        }
+
+        let inliers = self.inliers(points);
+        if inliers.is_empty() {
+            return Err(TransmissionLocaionError::NoMatches);
+        }
+
+        Ok(Estimate {
+            position: mean(&select(points, &inliers)),
+            inliers,
+        })
+    }
+}
+
+/// Computes the geometric median (the point with the smallest sum of distances to all points) of
+/// the points within `radius` meters of the geometric median of all points. The geometric median
+/// stays in place as long as less than half of the points are outliers.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GeometricMedian {
+    /// maximum distance in meters from the geometric median
+    pub radius: f64,
+    /// maximum number of iterations of the Weiszfeld algorithm
+    pub max_iterations: usize,
+    /// the algorithm stops once the estimate moves less than this many meters
+    pub tolerance: f64,
+}
+
+impl Default for GeometricMedian {
+    fn default() -> Self {
+        GeometricMedian {
+            radius: DEFAULT_INTERPOLATION_DISTANCE,
+            max_iterations: 100,
+            tolerance: 0.01,
+        }
+    }
+}
+
+impl GeometricMedian {
+    /// Approximates the geometric median with the Weiszfeld algorithm.
+    fn median(&self, points: &[(f64, f64)]) -> (f64, f64) {
+        let plane = Plane::new(mean(points));
+        let projected: Vec<(f64, f64)> = points.iter().map(|point| plane.project(*point)).collect();
+
+        let mut estimate = mean(&projected);
+        for _ in 0..self.max_iterations {
+            let (mut x, mut y, mut weights) = (0_f64, 0_f64, 0_f64);
+            for (px, py) in &projected {
+                // points on top of the estimate would get an infinite weight
+                let weight = 1_f64 / (px - estimate.0).hypot(py - estimate.1).max(1e-6);
+                x += px * weight;
+                y += py * weight;
+                weights += weight;
+            }
+
+            let next = (x / weights, y / weights);
+            let step = (next.0 - estimate.0).hypot(next.1 - estimate.1);
+            estimate = next;
+            if step < self.tolerance {
+                break;
+            }
+        }
+
+        plane.unproject(estimate)
+    }
+}
+
+impl LocationEstimator for GeometricMedian {
+    fn estimate(&self, points: &[(f64, f64)]) -> Result<Estimate, TransmissionLocaionError> {
+        if points.is_empty() {
+            return Err(TransmissionLocaionError::EmptyInput);
+        }
+
+        let all: Vec<usize> = (0..points.len()).collect();
+        let inliers = within(points, &all, self.median(points), self.radius);
+        if inliers.is_empty() {
+            return Err(TransmissionLocaionError::NoMatches);
+        }
+
+        Ok(Estimate {
+            position: self.median(&select(points, &inliers)),
+            inliers,
+        })
+    }
+}
+
+/// Repeatedly removes points whose distance from the mean is more than `sigma` standard
+/// deviations above the average distance, until no more points are removed. Points within
+/// `min_radius` meters of the mean are never removed, so a tight cluster doesn't get whittled
+/// away.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SigmaClipping {
+    /// number of standard deviations above the average distance at which points are removed
+    pub sigma: f64,
+    /// distance in meters from the mean within which points are always kept
+    pub min_radius: f64,
+    /// maximum number of clipping rounds
+    pub max_iterations: usize,
+}
+
+impl Default for SigmaClipping {
+    fn default() -> Self {
+        SigmaClipping {
+            sigma: 2_f64,
+            min_radius: 10_f64,
+            max_iterations: 10,
+        }
+    }
+}
+
+impl LocationEstimator for SigmaClipping {
+    fn estimate(&self, points: &[(f64, f64)]) -> Result<Estimate, TransmissionLocaionError> {
+        if points.is_empty() {
+            return Err(TransmissionLocaionError::EmptyInput);
+        }
+
+        let mut inliers: Vec<usize> = (0..points.len()).collect();
+        for _ in 0..self.max_iterations {
+            let center = mean(&select(points, &inliers));
+            let distances: Vec<f64> = inliers
+                .iter()
+                .map(|index| points[*index].distance_from(center))
+                .collect();
+            let average = distances.iter().sum::<f64>() / distances.len() as f64;
+            let deviation = (distances
+                .iter()
+                .map(|distance| (distance - average).powi(2))
+                .sum::<f64>()
+                / distances.len() as f64)
+                .sqrt();
+            let threshold = (average + self.sigma * deviation).max(self.min_radius);
+
+            // the closest point is never further away than the average, so this can't be empty
+            let kept = within(points, &inliers, center, threshold);
+            if kept.len() == inliers.len() {
+                break;
+            }
+            inliers = kept;
+        }
+
+        Ok(Estimate {
+            position: mean(&select(points, &inliers)),
+            inliers,
+        })
+    }
+}
+
+/// Clusters the points like DBSCAN and averages the biggest cluster. A point belongs to a cluster
+/// if it has at least `min_points` neighbours (including itself) within `epsilon` meters or is
+/// such a neighbour, all other points are noise. Works even if most points are outliers, as long
+/// as they are scattered.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Dbscan {
+    /// radius in meters within which points are neighbours
+    pub epsilon: f64,
+    /// minimal number of neighbours that form the core of a cluster
+    pub min_points: usize,
+}
+
+impl Default for Dbscan {
+    fn default() -> Self {
+        Dbscan {
+            epsilon: 20_f64,
+            min_points: 3,
+        }
+    }
+}
+
+impl Dbscan {
+    /// Returns the cluster of every point, noise has no cluster.
+    fn clusters(&self, points: &[(f64, f64)]) -> Vec<Option<usize>> {
+        let neighbours: Vec<Vec<usize>> = points
+            .iter()
+            .map(|point| {
+                (0..points.len())
+                    .filter(|other| point.distance_from(points[*other]) <= self.epsilon)
+                    .collect()
+            })
+            .collect();
+
+        let mut clusters: Vec<Option<usize>> = vec![None; points.len()];
+        let mut visited = vec![false; points.len()];
+        let mut next_cluster = 0;
+
+        for start in 0..points.len() {
+            if visited[start] || neighbours[start].len() < self.min_points {
+                continue;
+            }
+
+            let mut queue = vec![start];
+            visited[start] = true;
+            while let Some(point) = queue.pop() {
+                clusters[point] = Some(next_cluster);
+                if neighbours[point].len() < self.min_points {
+                    // border point, doesn't expand the cluster
+                    continue;
+                }
+                for neighbour in &neighbours[point] {
+                    if !visited[*neighbour] {
+                        visited[*neighbour] = true;
+                        queue.push(*neighbour);
+                    }
+                }
+            }
+            next_cluster += 1;
+        }
+
+        clusters
+    }
+}
+
+impl LocationEstimator for Dbscan {
+    fn estimate(&self, points: &[(f64, f64)]) -> Result<Estimate, TransmissionLocaionError> {
+        if points.is_empty() {
+            return Err(TransmissionLocaionError::EmptyInput);
+        }
+
+        let clusters = self.clusters(points);
+        let mut sizes: Vec<usize> = Vec::new();
+        for cluster in clusters.iter().flatten() {
+            if sizes.len() <= *cluster {
+                sizes.resize(cluster + 1, 0);
+            }
+            sizes[*cluster] += 1;
+        }
+
+        // the first cluster wins ties, so the result doesn't depend on anything but the order
+        let biggest = sizes
+            .iter()
+            .enumerate()
+            .rev()
+            .max_by_key(|(_, size)| **size)
+            .map(|(cluster, _)| cluster)
+            .ok_or(TransmissionLocaionError::NoMatches)?;
+
+        let members: Vec<usize> = (0..points.len())
+            .filter(|index| clusters[*index] == Some(biggest))
+            .collect();
+
+        Ok(Estimate {
+            position: mean(&select(points, &members)),
+            inliers: members,
+        })
+    }
+}
+
+/// Average of latitudes and longitudes, accurate enough for points a few hundred meters apart.
+fn mean(points: &[(f64, f64)]) -> (f64, f64) {
+    let (lats, lons): (Vec<f64>, Vec<f64>) = points.iter().copied().unzip();
+
+    (
+        lats.iter().sum::<f64>() / (lats.len() as f64),
+        lons.iter().sum::<f64>() / (lons.len() as f64),
+    )
+}
+
+/// Points at the given indices.
+fn select(points: &[(f64, f64)], indices: &[usize]) -> Vec<(f64, f64)> {
+    indices.iter().map(|index| points[*index]).collect()
+}
+
+/// Indices of the points at `indices` which are at most `radius` meters away from `center`.
+fn within(points: &[(f64, f64)], indices: &[usize], center: (f64, f64), radius: f64) -> Vec<usize> {
+    indices
+        .iter()
+        .copied()
+        .filter(|index| points[*index].distance_from(center) <= radius)
+        .collect()
+}
+
+/// Equirectangular projection into meters around an origin, accurate enough for points a few
+/// kilometers apart.
+struct Plane {
+    origin: (f64, f64),
+    scale: f64,
+}
+
+impl Plane {
+    fn new(origin: (f64, f64)) -> Self {
+        Plane {
+            origin,
+            scale: origin.0.to_radians().cos(),
+        }
+    }
+
+    fn project(&self, (lat, lon): (f64, f64)) -> (f64, f64) {
+        let radius = MEAN_EARTH_RADIUS as f64;
+        (
+            (lon - self.origin.1).to_radians() * radius * self.scale,
+            (lat - self.origin.0).to_radians() * radius,
+        )
+    }
+
+    fn unproject(&self, (x, y): (f64, f64)) -> (f64, f64) {
+        let radius = MEAN_EARTH_RADIUS as f64;
+        (
+            self.origin.0 + (y / radius).to_degrees(),
+            self.origin.1 + (x / (radius * self.scale)).to_degrees(),
+        )
+    }
+}
@@ -0,0 +1,265 @@
+//!
+//! Cleans up gps points recorded by phones before they are correlated. Points with a poor
+//! accuracy, duplicate timestamps and physically impossible jumps are removed, the remaining
+//! points can be smoothed to get rid of the jitter while the vehicle is standing.
+//!
+
+use super::GpsPoint;
+use crate::locations::{DEFAULT_GPS_ACCURACY, DistanceFrom, MEAN_EARTH_RADIUS};
+
+use serde::{Deserialize, Serialize};
+
+/// Worst accuracy in meters of a gps point that is kept, used by [`CleaningConfig::default`].
+pub const DEFAULT_MAX_ACCURACY: f64 = 50_f64;
+/// Fastest speed in meters per second a vehicle is believed to drive, used by
+/// [`CleaningConfig::default`].
+pub const DEFAULT_MAX_SPEED: f64 = 40_f64;
+
+/// How the cleaned gps points are smoothed.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Default)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Smoothing {
+    /// The points are not smoothed.
+    #[default]
+    None,
+    /// Every point is moved to the average of the `window` points around it.
+    MovingAverage {
+        /// number of points averaged, including the point itself
+        window: usize,
+    },
+    /// Kalman filter assuming the vehicle drives with a constant velocity that is changed by
+    /// random accelerations, so the positions of a moving vehicle don't lag behind. The measured
+    /// accuracy of the points is kept.
+    Kalman {
+        /// spectral density of the random acceleration in square meters per cubic second, larger
+        /// values follow changes of the velocity faster
+        process_noise: f64,
+    },
+}
+
+/// Configures how gps points are cleaned.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CleaningConfig {
+    /// points with an accuracy worse than this many meters are removed, points without an accuracy
+    /// are kept
+    pub max_accuracy: f64,
+    /// points that can only be reached faster than this many meters per second are removed
+    pub max_speed: f64,
+    /// smoothing applied after the cleaning
+    pub smoothing: Smoothing,
+}
+
+impl Default for CleaningConfig {
+    fn default() -> Self {
+        CleaningConfig {
+            max_accuracy: DEFAULT_MAX_ACCURACY,
+            max_speed: DEFAULT_MAX_SPEED,
+            smoothing: Smoothing::default(),
+        }
+    }
+}
+
+/// Number of points [`clean`] removed for every reason.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CleaningReport {
+    /// points with an accuracy worse than [`CleaningConfig::max_accuracy`]
+    pub poor_accuracy: usize,
+    /// points with the same timestamp as a more accurate point
+    pub duplicates: usize,
+    /// points that can't be reached without driving faster than [`CleaningConfig::max_speed`]
+    pub speed_jumps: usize,
+}
+
+impl CleaningReport {
+    /// Total number of removed points.
+    pub fn removed(&self) -> usize {
+        self.poor_accuracy + self.duplicates + self.speed_jumps
+    }
+}
+
+/// Sorts the gps points by their timestamp, removes points with a poor accuracy, duplicate
+/// timestamps and impossible jumps and smoothes the remaining points as configured.
+pub fn clean(
+    mut points: Vec<GpsPoint>,
+    config: &CleaningConfig,
+) -> (Vec<GpsPoint>, CleaningReport) {
+    let mut report = CleaningReport::default();
+    points.sort_by_key(|point| point.timestamp);
+
+    let count = points.len();
+    points.retain(|point| {
+        point
+            .accuracy
+            .is_none_or(|accuracy| accuracy <= config.max_accuracy)
+    });
+    report.poor_accuracy = count - points.len();
+
+    let count = points.len();
+    let mut points = deduplicate(points);
+    report.duplicates = count - points.len();
+
+    let count = points.len();
+    remove_jumps(&mut points, config.max_speed);
+    report.speed_jumps = count - points.len();
+
+    match config.smoothing {
+        Smoothing::None => {}
+        Smoothing::MovingAverage { window } => moving_average(&mut points, window),
+        Smoothing::Kalman { process_noise } => kalman(&mut points, process_noise, config.max_speed),
+    }
+
+    (points, report)
+}
+
+/// Keeps the most accurate of the sorted points sharing a timestamp, unknown accuracies lose.
+fn deduplicate(points: Vec<GpsPoint>) -> Vec<GpsPoint> {
+    let mut unique: Vec<GpsPoint> = Vec::with_capacity(points.len());
+    for point in points {
+        match unique.last_mut() {
+            Some(last) if last.timestamp == point.timestamp => {
+                let accuracy = |point: &GpsPoint| point.accuracy.unwrap_or(f64::INFINITY);
+                if accuracy(&point) < accuracy(last) {
+                    *last = point;
+                }
+            }
+            _ => unique.push(point),
+        }
+    }
+
+    unique
+}
+
+/// Speed in meters per second needed to get from one point to the other.
+fn speed(from: &GpsPoint, to: &GpsPoint) -> f64 {
+    let distance = (from.lat, from.lon).distance_from((to.lat, to.lon));
+    let seconds = (to.timestamp - from.timestamp).num_milliseconds() as f64 / 1000_f64;
+
+    distance / seconds
+}
+
+/// Removes every point that can't be reached from the last kept point without driving faster than
+/// `max_speed`. The first point is only kept if the following points confirm it.
+fn remove_jumps(points: &mut Vec<GpsPoint>, max_speed: f64) {
+    // a jump between the first two points which the third agrees with is the first one's fault
+    if points.len() >= 3
+        && speed(&points[0], &points[1]) > max_speed
+        && speed(&points[1], &points[2]) <= max_speed
+    {
+        points.remove(0);
+    }
+
+    let mut kept: Vec<GpsPoint> = Vec::with_capacity(points.len());
+    for point in points.drain(..) {
+        if kept
+            .last()
+            .is_none_or(|last| speed(last, &point) <= max_speed)
+        {
+            kept.push(point);
+        }
+    }
+    *points = kept;
+}
+
+/// Moves every point to the average of the `window` points centered around it, the window
+/// shrinks at both ends of the track.
+fn moving_average(points: &mut [GpsPoint], window: usize) {
+    let radius = window / 2;
+    let original: Vec<(f64, f64)> = points.iter().map(|point| (point.lat, point.lon)).collect();
+
+    for (index, point) in points.iter_mut().enumerate() {
+        let neighbours =
+            &original[index.saturating_sub(radius)..(index + radius + 1).min(original.len())];
+        point.lat = neighbours.iter().map(|(lat, _)| lat).sum::<f64>() / neighbours.len() as f64;
+        point.lon = neighbours.iter().map(|(_, lon)| lon).sum::<f64>() / neighbours.len() as f64;
+    }
+}
+
+/// Runs a Kalman filter with a constant velocity model over the points, see
+/// [`Smoothing::Kalman`]. North and east are filtered independently in meters around the first
+/// point, the velocity starts at zero with a standard deviation of `max_speed`. Points without an
+/// accuracy are assumed to have [`DEFAULT_GPS_ACCURACY`].
+fn kalman(points: &mut [GpsPoint], process_noise: f64, max_speed: f64) {
+    let Some(first) = points.first() else {
+        return;
+    };
+    let origin = (first.lat, first.lon);
+    let radius = MEAN_EARTH_RADIUS as f64;
+    let scale = origin.0.to_radians().cos();
+
+    let initial = first.accuracy.unwrap_or(DEFAULT_GPS_ACCURACY).powi(2);
+    let mut north = ConstantVelocity::new(initial, max_speed.powi(2));
+    let mut east = ConstantVelocity::new(initial, max_speed.powi(2));
+    let mut time = first.timestamp;
+
+    for point in points.iter_mut() {
+        let seconds = (point.timestamp - time).num_milliseconds() as f64 / 1000_f64;
+        time = point.timestamp;
+
+        let measurement = point.accuracy.unwrap_or(DEFAULT_GPS_ACCURACY).powi(2);
+        let lat = north.step(
+            (point.lat - origin.0).to_radians() * radius,
+            measurement,
+            seconds,
+            process_noise,
+        );
+        let lon = east.step(
+            (point.lon - origin.1).to_radians() * radius * scale,
+            measurement,
+            seconds,
+            process_noise,
+        );
+
+        point.lat = origin.0 + (lat / radius).to_degrees();
+        point.lon = origin.1 + (lon / (radius * scale)).to_degrees();
+    }
+}
+
+/// State of a one dimensional Kalman filter tracking position and velocity.
+struct ConstantVelocity {
+    position: f64,
+    velocity: f64,
+    /// covariance matrix `[[position, both], [both, velocity]]`
+    covariance: (f64, f64, f64),
+}
+
+impl ConstantVelocity {
+    /// Starts at position zero without moving.
+    fn new(position_variance: f64, velocity_variance: f64) -> Self {
+        ConstantVelocity {
+            position: 0_f64,
+            velocity: 0_f64,
+            covariance: (position_variance, 0_f64, velocity_variance),
+        }
+    }
+
+    /// Predicts the state `seconds` ahead, corrects it with the `measurement` of the position and
+    /// returns the filtered position.
+    fn step(&mut self, measurement: f64, variance: f64, seconds: f64, process_noise: f64) -> f64 {
+        let (p, pv, v) = self.covariance;
+        self.position += self.velocity * seconds;
+        let p = p
+            + 2_f64 * seconds * pv
+            + seconds.powi(2) * v
+            + process_noise * seconds.powi(3) / 3_f64;
+        let pv = pv + seconds * v + process_noise * seconds.powi(2) / 2_f64;
+        let v = v + process_noise * seconds;
+
+        // two perfectly accurate values can't be weighed against each other
+        let (position_gain, velocity_gain) = if p + variance > 0_f64 {
+            (p / (p + variance), pv / (p + variance))
+        } else {
+            (1_f64, 0_f64)
+        };
+        let residual = measurement - self.position;
+        self.position += position_gain * residual;
+        self.velocity += velocity_gain * residual;
+        self.covariance = (
+            (1_f64 - position_gain) * p,
+            (1_f64 - position_gain) * pv,
+            v - velocity_gain * pv,
+        );
+
+        self.position
+    }
+}
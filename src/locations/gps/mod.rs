@@ -0,0 +1,219 @@
+//! This module holds replresentations for geolocation data used all over the TLMS services
+
+pub mod clean;
+#[cfg(test)]
+pub(crate) mod tests;
+pub mod track;
+
+use crate::grpc::{GpsValidationError, GrpcGpsPoint};
+use crate::schema::*;
+#[cfg(feature = "trekkie")]
+use crate::timestamp::Timestamp;
+#[cfg(feature = "trekkie")]
+use crate::trekkie::TrekkieRun;
+use chrono::NaiveDateTime;
+use diesel::{Insertable, Queryable};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use std::fmt;
+
+/// Gps trackpoint representation used in database.
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, Queryable)]
+#[diesel(table_name = gps_points)]
+pub struct GpsPoint {
+    /// primary key bigserial
+    pub id: i64,
+    /// foreign key referencing the corresponding trekkie run
+    pub trekkie_run: Uuid,
+    /// point's timestamp
+    #[serde(with = "crate::time_serializer")]
+    pub timestamp: NaiveDateTime,
+    /// Latitude
+    pub lat: f64,
+    /// Longitude
+    pub lon: f64,
+    /// Optional elevation
+    pub elevation: Option<f64>,
+    /// Optional GPS accuracy
+    pub accuracy: Option<f64>,
+    /// Optional GPS vertical accuracy
+    pub vertical_accuracy: Option<f64>,
+    /// Optional GPS bearing (aka angle of the compas)
+    pub bearing: Option<f64>,
+    /// Optional speed
+    pub speed: Option<f64>,
+}
+
+/// Struct for inserting a gps point into the database and utilise the auto increment functions
+/// from postgres.
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, Insertable)]
+#[diesel(table_name = gps_points)]
+pub struct InsertGpsPoint {
+    /// primary key bigserial
+    pub id: Option<i64>,
+    /// foreign key referencing the corresponding trekkie run
+    pub trekkie_run: Uuid,
+    /// point's timestamp
+    #[serde(with = "crate::time_serializer")]
+    pub timestamp: NaiveDateTime,
+    /// Latitude
+    pub lat: f64,
+    /// Longitude
+    pub lon: f64,
+    /// Optional elevation
+    pub elevation: Option<f64>,
+    /// Optional GPS accuracy
+    pub accuracy: Option<f64>,
+    /// Optional GPS vertical accuracy
+    pub vertical_accuracy: Option<f64>,
+    /// Optional GPS bearing (aka angle of the compas)
+    pub bearing: Option<f64>,
+    /// Optional speed
+    pub speed: Option<f64>,
+}
+
+impl From<GpsPoint> for InsertGpsPoint {
+    fn from(val: GpsPoint) -> Self {
+        Self {
+            id: Some(val.id),
+            trekkie_run: val.trekkie_run,
+            timestamp: val.timestamp,
+            lat: val.lat,
+            lon: val.lon,
+            elevation: val.elevation,
+            accuracy: val.accuracy,
+            vertical_accuracy: val.vertical_accuracy,
+            bearing: val.bearing,
+            speed: val.speed,
+        }
+    }
+}
+
+/// Error returned when a [`GrpcGpsPoint`] can't be converted.
+#[derive(Debug, Clone, PartialEq)]
+pub enum GpsConversionError {
+    /// The id doesn't fit into the primary key.
+    InvalidId(u64),
+    /// The trekkie run is not a valid UUID.
+    InvalidTrekkieRun(uuid::Error),
+    /// The timestamp can not be represented as [`NaiveDateTime`].
+    InvalidTime(u64),
+    /// The timestamp lies before 1970 and can't be sent as unix time stamp.
+    TimeBeforeEpoch(NaiveDateTime),
+    /// Latitude or longitude are not a position on earth.
+    InvalidPosition(GpsValidationError),
+    /// The gps point was recorded during a different trekkie run.
+    RunMismatch {
+        /// trekkie run of the gps point
+        point: Uuid,
+        /// id of the trekkie run
+        run: Uuid,
+    },
+}
+
+impl fmt::Display for GpsConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GpsConversionError::InvalidId(id) => write!(f, "invalid gps point id: {id}"),
+            GpsConversionError::InvalidTrekkieRun(e) => write!(f, "invalid trekkie run uuid: {e}"),
+            GpsConversionError::InvalidTime(value) => write!(f, "invalid timestamp: {value}"),
+            GpsConversionError::TimeBeforeEpoch(time) => {
+                write!(f, "timestamp {time} lies before 1970")
+            }
+            GpsConversionError::InvalidPosition(e) => write!(f, "{e}"),
+            GpsConversionError::RunMismatch { point, run } => write!(
+                f,
+                "gps point of trekkie run {point} doesn't belong to trekkie run {run}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for GpsConversionError {}
+
+impl TryFrom<GrpcGpsPoint> for InsertGpsPoint {
+    type Error = GpsConversionError;
+
+    /// Line, run and region are dropped because they are stored in the trekkie run. An id of 0 is
+    /// treated as not set, so a point with `id: Some(0)` comes back with `id: None` after a round
+    /// trip.
+    fn try_from(point: GrpcGpsPoint) -> Result<Self, Self::Error> {
+        point
+            .validate()
+            .map_err(GpsConversionError::InvalidPosition)?;
+
+        let id = match point.id {
+            0 => None,
+            id => Some(i64::try_from(id).map_err(|_| GpsConversionError::InvalidId(id))?),
+        };
+        let timestamp = point
+            .get_time()
+            .map_err(|_| GpsConversionError::InvalidTime(point.time))?
+            .naive_utc();
+
+        Ok(InsertGpsPoint {
+            id,
+            trekkie_run: Uuid::parse_str(&point.trekkie_run)
+                .map_err(GpsConversionError::InvalidTrekkieRun)?,
+            timestamp,
+            lat: point.lat,
+            lon: point.lon,
+            elevation: point.elevation,
+            accuracy: point.accuracy,
+            vertical_accuracy: point.vertical_accuracy,
+            bearing: point.bearing,
+            speed: point.speed,
+        })
+    }
+}
+
+#[cfg(feature = "trekkie")]
+impl TryFrom<(InsertGpsPoint, &TrekkieRun)> for GrpcGpsPoint {
+    type Error = GpsConversionError;
+
+    /// Line, run and region are taken from the trekkie run the point was recorded during. Points
+    /// recorded before 1970 can't be sent.
+    ///
+    /// The message only carries unix milliseconds, the time stamp is truncated to whole
+    /// milliseconds. A missing or negative id is sent as 0 which means not set.
+    fn try_from((point, run): (InsertGpsPoint, &TrekkieRun)) -> Result<Self, Self::Error> {
+        if point.trekkie_run != run.id {
+            return Err(GpsConversionError::RunMismatch {
+                point: point.trekkie_run,
+                run: run.id,
+            });
+        }
+
+        Ok(GrpcGpsPoint {
+            time: u64::try_from(Timestamp::from(point.timestamp))
+                .map_err(|_| GpsConversionError::TimeBeforeEpoch(point.timestamp))?,
+            id: point
+                .id
+                .and_then(|id| u64::try_from(id).ok())
+                .unwrap_or_default(),
+            region: run.region,
+            lat: point.lat,
+            lon: point.lon,
+            line: run.line,
+            run: run.run,
+            elevation: point.elevation,
+            accuracy: point.accuracy,
+            vertical_accuracy: point.vertical_accuracy,
+            bearing: point.bearing,
+            speed: point.speed,
+            trekkie_run: point.trekkie_run.to_string(),
+        })
+    }
+}
+
+#[cfg(feature = "trekkie")]
+impl TryFrom<(GpsPoint, &TrekkieRun)> for GrpcGpsPoint {
+    type Error = GpsConversionError;
+
+    /// Same as the conversion of [`InsertGpsPoint`], the time stamp is truncated to whole
+    /// milliseconds.
+    fn try_from((point, run): (GpsPoint, &TrekkieRun)) -> Result<Self, Self::Error> {
+        GrpcGpsPoint::try_from((InsertGpsPoint::from(point), run))
+    }
+}
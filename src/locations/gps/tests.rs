@@ -0,0 +1,254 @@
+use super::track::*;
+use super::*;
+use crate::locations::DistanceFrom;
+
+/// Time `millis` milliseconds after the start of the test recordings.
+pub(crate) fn time(millis: i64) -> NaiveDateTime {
+    chrono::DateTime::from_timestamp_millis(1_681_000_000_000 + millis)
+        .unwrap()
+        .naive_utc()
+}
+
+/// Gps point of the nil trekkie run with an accuracy of 5 meters recorded at [`time`]`(millis)`.
+pub(crate) fn gps_point(millis: i64, lat: f64, lon: f64) -> GpsPoint {
+    GpsPoint {
+        id: millis,
+        trekkie_run: Uuid::nil(),
+        timestamp: time(millis),
+        lat,
+        lon,
+        elevation: None,
+        accuracy: Some(5.0),
+        vertical_accuracy: None,
+        bearing: None,
+        speed: None,
+    }
+}
+
+#[test]
+fn test_gps_track_new() {
+    assert_eq!(GpsTrack::new(Vec::new()).err(), Some(GpsTrackError::Empty));
+
+    let points = vec![
+        gps_point(0, 51.0, 13.0),
+        gps_point(2_000, 51.0, 13.0),
+        gps_point(1_000, 51.0, 13.0),
+    ];
+    assert_eq!(
+        GpsTrack::try_from(points.clone()).err(),
+        Some(GpsTrackError::Unsorted(2))
+    );
+
+    let track = GpsTrack::sorted(points).unwrap();
+    assert_eq!((track.get_start(), track.get_end()), (time(0), time(2_000)));
+    assert_eq!(track.get_points()[1].id, 1_000);
+}
+
+#[test]
+fn test_gps_track_interpolation() {
+    let mut slow = gps_point(10_000, 51.001, 13.002);
+    slow.accuracy = Some(12.0);
+    let track = GpsTrack::new(vec![
+        gps_point(0, 51.0, 13.0),
+        slow,
+        gps_point(60_000, 51.002, 13.004),
+    ])
+    .unwrap();
+
+    let position = track.position_at(time(2_500)).unwrap();
+    assert!((position.lat - 51.00025).abs() < 1e-9);
+    assert!((position.lon - 13.0005).abs() < 1e-9);
+    assert_eq!(position.accuracy, Some(12.0));
+    assert_eq!(position.time_offset, 2_500);
+    assert!(!position.extrapolated);
+
+    let position = track.position_at(time(10_000)).unwrap();
+    assert_eq!((position.lat, position.lon), (51.001, 13.002));
+    assert_eq!(position.time_offset, 0);
+
+    // great circle and linear interpolation agree on short distances
+    let great_circle = track
+        .clone()
+        .with_interpolation(Interpolation::GreatCircle)
+        .position_at(time(2_500))
+        .unwrap();
+    assert!((great_circle.lat, great_circle.lon).distance_from((51.00025, 13.0005)) < 0.01);
+
+    // the gap of 50 seconds is too long and the points don't know their speed
+    assert_eq!(
+        track.position_at(time(30_000)),
+        Err(GpsTrackError::GapTooLarge {
+            time: time(30_000),
+            gap: 50_000
+        })
+    );
+    assert!(track.with_max_gap(50_000).position_at(time(30_000)).is_ok());
+}
+
+#[test]
+fn test_gps_track_great_circle() {
+    // halfway between two points on the equator a quarter of the earth apart
+    let track = GpsTrack::new(vec![gps_point(0, 0.0, 0.0), gps_point(1_000, 0.0, 90.0)])
+        .unwrap()
+        .with_interpolation(Interpolation::GreatCircle);
+    let position = track.position_at(time(500)).unwrap();
+    assert!(position.lat.abs() < 1e-9);
+    assert!((position.lon - 45.0).abs() < 1e-9);
+
+    // the great circle between two points on the same latitude bulges towards the pole
+    let track = GpsTrack::new(vec![gps_point(0, 60.0, 0.0), gps_point(1_000, 60.0, 90.0)])
+        .unwrap()
+        .with_interpolation(Interpolation::GreatCircle);
+    let position = track.position_at(time(500)).unwrap();
+    assert!(position.lat > 60.0);
+    assert!((position.lon - 45.0).abs() < 1e-9);
+}
+
+#[test]
+fn test_gps_track_extrapolation() {
+    let mut last = gps_point(10_000, 51.0, 13.0);
+    last.speed = Some(10.0);
+    last.bearing = Some(0.0);
+    let track = GpsTrack::new(vec![gps_point(0, 51.0, 13.0), last]).unwrap();
+
+    // heading north with 10 meters per second
+    let position = track.position_at(time(13_000)).unwrap();
+    assert!(position.extrapolated);
+    assert_eq!(position.time_offset, 3_000);
+    assert!((position.lon - 13.0).abs() < 1e-9);
+    assert!(position.lat > 51.0);
+    assert!(((position.lat, position.lon).distance_from((51.0, 13.0)) - 30.0).abs() < 0.01);
+
+    assert_eq!(
+        track.position_at(time(16_000)),
+        Err(GpsTrackError::OutsideTrack(time(16_000)))
+    );
+    // the first point doesn't know its speed
+    assert_eq!(
+        track.position_at(time(-1_000)),
+        Err(GpsTrackError::OutsideTrack(time(-1_000)))
+    );
+
+    // inside a gap the closer point is used
+    let mut first = gps_point(0, 51.0, 13.0);
+    first.speed = Some(10.0);
+    first.bearing = Some(90.0);
+    let track = GpsTrack::new(vec![first, gps_point(60_000, 51.0, 13.01)]).unwrap();
+    let position = track.position_at(time(2_000)).unwrap();
+    assert!(position.extrapolated);
+    assert!(((position.lat, position.lon).distance_from((51.0, 13.0)) - 20.0).abs() < 0.01);
+    assert!(
+        track
+            .with_max_extrapolation(0)
+            .position_at(time(2_000))
+            .is_err()
+    );
+}
+
+#[test]
+fn test_clean() {
+    use clean::{CleaningConfig, CleaningReport, clean};
+
+    let with_accuracy = |millis: i64, east: f64, accuracy: Option<f64>| {
+        let mut point = gps_point(millis, 51.0, 13.0 + east / 70_000.0);
+        point.accuracy = accuracy;
+        point
+    };
+    // driving east with about 10 meters per second
+    let points = vec![
+        with_accuracy(2_000, 20.0, Some(5.0)),
+        with_accuracy(0, 0.0, Some(5.0)),
+        with_accuracy(1_000, 10.0, Some(80.0)),
+        with_accuracy(2_000, 21.0, Some(3.0)),
+        with_accuracy(2_000, 22.0, None),
+        // jump of a kilometer within a second
+        with_accuracy(3_000, 1000.0, Some(5.0)),
+        with_accuracy(4_000, 40.0, None),
+    ];
+
+    let (cleaned, report) = clean(points, &CleaningConfig::default());
+    assert_eq!(
+        report,
+        CleaningReport {
+            poor_accuracy: 1,
+            duplicates: 2,
+            speed_jumps: 1,
+        }
+    );
+    assert_eq!(report.removed(), 4);
+
+    let times: Vec<i64> = cleaned.iter().map(|point| point.id).collect();
+    assert_eq!(times, vec![0, 2_000, 4_000]);
+    // the most accurate duplicate wins
+    assert_eq!(cleaned[1].accuracy, Some(3.0));
+}
+
+#[test]
+fn test_clean_bad_first_point() {
+    use clean::{CleaningConfig, clean};
+
+    let points = vec![
+        gps_point(0, 51.1, 13.0),
+        gps_point(1_000, 51.0, 13.0),
+        gps_point(2_000, 51.0, 13.0001),
+        gps_point(3_000, 51.0, 13.0002),
+    ];
+
+    let (cleaned, report) = clean(points, &CleaningConfig::default());
+    assert_eq!(report.speed_jumps, 1);
+    assert_eq!(cleaned.len(), 3);
+    assert_eq!(cleaned[0].id, 1_000);
+}
+
+#[test]
+fn test_clean_smoothing() {
+    use clean::{CleaningConfig, Smoothing, clean};
+
+    // standing still with jitter of a few meters
+    let jitter = [0.0, 4.0, -4.0, 2.0, -2.0, 0.0];
+    let points: Vec<GpsPoint> = jitter
+        .iter()
+        .enumerate()
+        .map(|(index, north)| gps_point(index as i64 * 1_000, 51.0 + north / 111_000.0, 13.0))
+        .collect();
+    let spread = |points: &[GpsPoint]| {
+        points
+            .iter()
+            .map(|point| (point.lat, point.lon).distance_from((51.0, 13.0)))
+            .fold(0_f64, f64::max)
+    };
+    assert!(spread(&points) > 3.5);
+
+    let config = CleaningConfig {
+        smoothing: Smoothing::MovingAverage { window: 3 },
+        ..CleaningConfig::default()
+    };
+    let (smoothed, _) = clean(points.clone(), &config);
+    assert_eq!(smoothed.len(), points.len());
+    assert!(spread(&smoothed) <= 2.01);
+    // the window shrinks at the start
+    assert!((smoothed[0].lat - (51.0 + 2.0 / 111_000.0)).abs() < 1e-9);
+
+    let config = CleaningConfig {
+        smoothing: Smoothing::Kalman { process_noise: 1.0 },
+        ..CleaningConfig::default()
+    };
+    let (smoothed, _) = clean(points, &config);
+    // the second fix is needed to learn the velocity, the jitter is smoothed afterwards
+    assert!(spread(&smoothed[2..]) < 2.0);
+    // the measured accuracy is kept
+    assert!(smoothed.iter().all(|point| point.accuracy == Some(5.0)));
+
+    // driving north with 10 m/s, the filter doesn't lag behind
+    let points: Vec<GpsPoint> = (0..20)
+        .map(|index| gps_point(index * 1_000, 51.0 + index as f64 * 10.0 / 111_000.0, 13.0))
+        .collect();
+    let (smoothed, _) = clean(points.clone(), &config);
+    let last = (smoothed[19].lat, smoothed[19].lon);
+    assert!(last.distance_from((points[19].lat, points[19].lon)) < 1.0);
+
+    let config: CleaningConfig =
+        serde_json::from_str(r#"{"smoothing": {"type": "moving_average", "window": 5}}"#).unwrap();
+    assert_eq!(config.smoothing, Smoothing::MovingAverage { window: 5 });
+    assert_eq!(config.max_speed, clean::DEFAULT_MAX_SPEED);
+}
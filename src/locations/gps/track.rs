@@ -0,0 +1,287 @@
+//!
+//! Looks up where the vehicle of a recording was at any point in time. Positions between two gps
+//! points are interpolated, shortly before, after or inside a gap of the recording they are
+//! extrapolated from the speed and bearing of the closest gps point.
+//!
+
+use super::GpsPoint;
+use crate::locations::MEAN_EARTH_RADIUS;
+
+use chrono::NaiveDateTime;
+
+use std::fmt;
+
+/// Maximum time in milliseconds between two gps points a position is interpolated between, used
+/// by [`GpsTrack::new`].
+pub const DEFAULT_MAX_GAP: i64 = 30_000;
+/// Maximum time in milliseconds a position is extrapolated from a single gps point, used by
+/// [`GpsTrack::new`].
+pub const DEFAULT_MAX_EXTRAPOLATION: i64 = 5_000;
+
+/// How positions between two gps points are interpolated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Interpolation {
+    /// Interpolates latitude and longitude linearly, accurate enough for gps points a few hundred
+    /// meters apart.
+    #[default]
+    Linear,
+    /// Interpolates along the great circle between the gps points.
+    GreatCircle,
+}
+
+/// Error returned when a [`GpsTrack`] can't be created or doesn't know a position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GpsTrackError {
+    /// The track doesn't contain any gps points.
+    Empty,
+    /// The gps point at this index is older than the one before it.
+    Unsorted(usize),
+    /// The time is too long before the first or after the last gps point.
+    OutsideTrack(NaiveDateTime),
+    /// The time lies in a gap of the recording that is too long.
+    GapTooLarge {
+        /// requested time
+        time: NaiveDateTime,
+        /// milliseconds between the gps points around the time
+        gap: i64,
+    },
+}
+
+impl fmt::Display for GpsTrackError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GpsTrackError::Empty => write!(f, "gps track is empty"),
+            GpsTrackError::Unsorted(index) => {
+                write!(f, "gps point {index} is older than the one before it")
+            }
+            GpsTrackError::OutsideTrack(time) => write!(f, "{time} is outside the gps track"),
+            GpsTrackError::GapTooLarge { time, gap } => {
+                write!(f, "{time} lies in a gap of {gap}ms in the gps track")
+            }
+        }
+    }
+}
+
+impl std::error::Error for GpsTrackError {}
+
+/// Position of the vehicle returned by [`GpsTrack::position_at`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TrackPosition {
+    /// Latitude
+    pub lat: f64,
+    /// Longitude
+    pub lon: f64,
+    /// Accuracy in meters of the worse gps point the position was calculated from
+    pub accuracy: Option<f64>,
+    /// Time in milliseconds between the requested time and the closest gps point
+    pub time_offset: i64,
+    /// True if the position was extrapolated from a single gps point
+    pub extrapolated: bool,
+}
+
+/// Gps points of a recording sorted by their timestamp.
+#[derive(Debug, Clone)]
+pub struct GpsTrack {
+    points: Vec<GpsPoint>,
+    max_gap: i64,
+    max_extrapolation: i64,
+    interpolation: Interpolation,
+}
+
+impl TryFrom<Vec<GpsPoint>> for GpsTrack {
+    type Error = GpsTrackError;
+
+    fn try_from(points: Vec<GpsPoint>) -> Result<Self, Self::Error> {
+        GpsTrack::new(points)
+    }
+}
+
+impl GpsTrack {
+    /// Creates the track from gps points sorted by their timestamp, with [`DEFAULT_MAX_GAP`],
+    /// [`DEFAULT_MAX_EXTRAPOLATION`] and linear interpolation.
+    pub fn new(points: Vec<GpsPoint>) -> Result<Self, GpsTrackError> {
+        if points.is_empty() {
+            return Err(GpsTrackError::Empty);
+        }
+        if let Some(index) = points
+            .windows(2)
+            .position(|pair| pair[1].timestamp < pair[0].timestamp)
+        {
+            return Err(GpsTrackError::Unsorted(index + 1));
+        }
+
+        Ok(GpsTrack {
+            points,
+            max_gap: DEFAULT_MAX_GAP,
+            max_extrapolation: DEFAULT_MAX_EXTRAPOLATION,
+            interpolation: Interpolation::default(),
+        })
+    }
+
+    /// Same as [`GpsTrack::new`] but sorts the gps points first.
+    pub fn sorted(mut points: Vec<GpsPoint>) -> Result<Self, GpsTrackError> {
+        points.sort_by_key(|point| point.timestamp);
+        GpsTrack::new(points)
+    }
+
+    /// Sets the maximum milliseconds between two gps points a position is interpolated between.
+    pub fn with_max_gap(mut self, max_gap: i64) -> Self {
+        self.max_gap = max_gap;
+        self
+    }
+
+    /// Sets the maximum milliseconds a position is extrapolated from a single gps point, zero
+    /// disables the extrapolation.
+    pub fn with_max_extrapolation(mut self, max_extrapolation: i64) -> Self {
+        self.max_extrapolation = max_extrapolation;
+        self
+    }
+
+    /// Sets how positions between two gps points are interpolated.
+    pub fn with_interpolation(mut self, interpolation: Interpolation) -> Self {
+        self.interpolation = interpolation;
+        self
+    }
+
+    /// The gps points of the track.
+    pub fn get_points(&self) -> &[GpsPoint] {
+        &self.points
+    }
+
+    /// Timestamp of the first gps point.
+    pub fn get_start(&self) -> NaiveDateTime {
+        self.points[0].timestamp
+    }
+
+    /// Timestamp of the last gps point.
+    pub fn get_end(&self) -> NaiveDateTime {
+        self.points[self.points.len() - 1].timestamp
+    }
+
+    /// Returns the position of the vehicle at `time`. Between two gps points at most the maximum
+    /// gap apart the position is interpolated. Otherwise it is extrapolated from the speed and
+    /// bearing of the closest gps point, if that point has them and is at most the maximum
+    /// extrapolation away.
+    pub fn position_at(&self, time: NaiveDateTime) -> Result<TrackPosition, GpsTrackError> {
+        let index = self.points.partition_point(|point| point.timestamp <= time);
+        let before = index.checked_sub(1).map(|index| &self.points[index]);
+        let after = self.points.get(index);
+
+        match (before, after) {
+            (Some(before), _) if before.timestamp == time => Ok(TrackPosition {
+                lat: before.lat,
+                lon: before.lon,
+                accuracy: before.accuracy,
+                time_offset: 0,
+                extrapolated: false,
+            }),
+            (Some(before), Some(after)) => {
+                let gap = (after.timestamp - before.timestamp).num_milliseconds();
+                if gap <= self.max_gap {
+                    return Ok(self.interpolate(before, after, time));
+                }
+
+                let closest = if time - before.timestamp <= after.timestamp - time {
+                    before
+                } else {
+                    after
+                };
+                self.extrapolate(closest, time)
+                    .ok_or(GpsTrackError::GapTooLarge { time, gap })
+            }
+            (Some(closest), None) | (None, Some(closest)) => self
+                .extrapolate(closest, time)
+                .ok_or(GpsTrackError::OutsideTrack(time)),
+            (None, None) => Err(GpsTrackError::Empty),
+        }
+    }
+
+    /// Interpolates the position at `time` between two gps points.
+    fn interpolate(
+        &self,
+        before: &GpsPoint,
+        after: &GpsPoint,
+        time: NaiveDateTime,
+    ) -> TrackPosition {
+        let gap = (after.timestamp - before.timestamp).num_milliseconds();
+        let elapsed = (time - before.timestamp).num_milliseconds();
+        let fraction = elapsed as f64 / gap as f64;
+
+        let (lat, lon) = match self.interpolation {
+            Interpolation::Linear => (
+                before.lat + (after.lat - before.lat) * fraction,
+                before.lon + (after.lon - before.lon) * fraction,
+            ),
+            Interpolation::GreatCircle => {
+                great_circle((before.lat, before.lon), (after.lat, after.lon), fraction)
+            }
+        };
+
+        TrackPosition {
+            lat,
+            lon,
+            // the worse of both fixes, an unknown accuracy stays unknown
+            accuracy: before
+                .accuracy
+                .zip(after.accuracy)
+                .map(|(before, after)| before.max(after)),
+            time_offset: elapsed.min(gap - elapsed),
+            extrapolated: false,
+        }
+    }
+
+    /// Moves from `point` along its bearing with its speed until `time`. Returns `None` if the
+    /// point doesn't know its speed and bearing or `time` is too far away.
+    fn extrapolate(&self, point: &GpsPoint, time: NaiveDateTime) -> Option<TrackPosition> {
+        let elapsed = (time - point.timestamp).num_milliseconds();
+        if elapsed.abs() > self.max_extrapolation {
+            return None;
+        }
+
+        let distance = point.speed? * elapsed as f64 / 1000_f64;
+        let (lat, lon) = destination((point.lat, point.lon), point.bearing?, distance);
+
+        Some(TrackPosition {
+            lat,
+            lon,
+            accuracy: point.accuracy,
+            time_offset: elapsed.abs(),
+            extrapolated: true,
+        })
+    }
+}
+
+/// Point at `fraction` of the way along the great circle from `from` to `to`.
+fn great_circle(from: (f64, f64), to: (f64, f64), fraction: f64) -> (f64, f64) {
+    let vector = |(lat, lon): (f64, f64)| {
+        let (lat, lon) = (lat.to_radians(), lon.to_radians());
+        [lat.cos() * lon.cos(), lat.cos() * lon.sin(), lat.sin()]
+    };
+    let (a, b) = (vector(from), vector(to));
+    let angle = (a[0] * b[0] + a[1] * b[1] + a[2] * b[2])
+        .clamp(-1_f64, 1_f64)
+        .acos();
+    if angle < 1e-12 {
+        return from;
+    }
+
+    let weight_a = ((1_f64 - fraction) * angle).sin() / angle.sin();
+    let weight_b = (fraction * angle).sin() / angle.sin();
+    let [x, y, z] = [0, 1, 2].map(|axis| weight_a * a[axis] + weight_b * b[axis]);
+
+    (z.atan2(x.hypot(y)).to_degrees(), y.atan2(x).to_degrees())
+}
+
+/// Point `distance` meters away from `from` in the direction of `bearing` degrees, negative
+/// distances go the opposite way.
+fn destination(from: (f64, f64), bearing: f64, distance: f64) -> (f64, f64) {
+    let (lat, lon) = (from.0.to_radians(), from.1.to_radians());
+    let bearing = bearing.to_radians();
+    let angle = distance / MEAN_EARTH_RADIUS as f64;
+
+    let dest_lat = (lat.sin() * angle.cos() + lat.cos() * angle.sin() * bearing.cos()).asin();
+    let dest_lon = lon
+        + (bearing.sin() * angle.sin() * lat.cos()).atan2(angle.cos() - lat.sin() * dest_lat.sin());
+
+    (dest_lat.to_degrees(), dest_lon.to_degrees())
+}
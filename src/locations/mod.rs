@@ -1,20 +1,38 @@
+pub mod estimator;
 pub mod gps;
+pub mod recompute;
 pub mod region;
+#[cfg(test)]
 mod tests;
 pub mod waypoint;
 
 use crate::schema::*;
+use estimator::{LocationEstimator, MeanFilter};
 
+use chrono::{NaiveDateTime, Utc};
 use diesel::{AsChangeset, Identifiable, Insertable, Queryable};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use utoipa::ToSchema;
 
 /// Version of the [`LocationsJson`] shcema used.
 pub const SCHEMA: &str = "3"; // INCREMENT ME ON ANY BREAKING CHANGE!!!!11111one
 
 /// maximum distance in meters
+#[deprecated(
+    since = "0.10.0",
+    note = "Please use LocationInferenceConfig from now on!"
+)]
 pub const SANE_INTERPOLATION_DISTANCE: i32 = 50;
+/// Maximum distance in meters between a raw position and the transmission location it belongs to,
+/// used by [`LocationInferenceConfig::default`] and the estimators.
+pub const DEFAULT_INTERPOLATION_DISTANCE: f64 = 50_f64;
+/// Accuracy in meters assumed for raw locations which don't know the accuracy of their gps fix,
+/// used by [`LocationInferenceConfig::default`].
+pub const DEFAULT_GPS_ACCURACY: f64 = 10_f64;
+/// Speed in meters per second at which the uncertainty of a raw location grows with the time
+/// between its gps fix and the telegram, used by [`LocationInferenceConfig::default`].
+pub const DEFAULT_TIME_OFFSET_SPEED: f64 = 10_f64;
 /// Mean earth radius, required for calcuation of distances between the GPS points
 pub const MEAN_EARTH_RADIUS: u32 = 6_371_000;
 
@@ -41,6 +59,19 @@ pub struct TransmissionLocation {
     /// If this transmission postion inserted from absolute data, and all the inference for it
     /// should be ignored
     pub ground_truth: bool,
+    /// Radius in meters around the location the true position is expected in, [`None`] if it
+    /// wasn't inferred by [`InsertTransmissionLocation::try_from_raw`]
+    pub uncertainty: Option<f64>,
+    /// Number of raw locations this location was inferred from
+    pub sample_count: i32,
+    /// Root mean square distance in meters between the raw locations and this location
+    pub spread: Option<f64>,
+    /// Number of distinct trekkie runs the raw locations came from
+    pub run_count: i32,
+    /// Number of distinct users who recorded the raw locations
+    pub owner_count: i32,
+    /// Time (UTC) this location was last inferred
+    pub last_updated: Option<NaiveDateTime>,
 }
 
 /// This struct is used to insert R09 telegram transmission positions to the database. Every entry
@@ -62,6 +93,19 @@ pub struct InsertTransmissionLocation {
     /// If this transmission postion inserted from absolute data, and all the inference for it
     /// should be ignored
     pub ground_truth: bool,
+    /// Radius in meters around the location the true position is expected in, [`None`] if it
+    /// wasn't inferred by [`InsertTransmissionLocation::try_from_raw`]
+    pub uncertainty: Option<f64>,
+    /// Number of raw locations this location was inferred from
+    pub sample_count: i32,
+    /// Root mean square distance in meters between the raw locations and this location
+    pub spread: Option<f64>,
+    /// Number of distinct trekkie runs the raw locations came from
+    pub run_count: i32,
+    /// Number of distinct users who recorded the raw locations
+    pub owner_count: i32,
+    /// Time (UTC) this location was last inferred
+    pub last_updated: Option<NaiveDateTime>,
 }
 
 /// This struct queries the database for transmission locations inferred from every single trekkie
@@ -83,6 +127,10 @@ pub struct TransmissionLocationRaw {
     pub trekkie_run: uuid::Uuid,
     /// User, from whose trekkie run this undeduped location was inferred
     pub run_owner: uuid::Uuid,
+    /// Accuracy in meters of the gps fix this location was interpolated from
+    pub accuracy: Option<f64>,
+    /// Time in milliseconds between the gps fix and the telegram
+    pub time_offset: Option<i64>,
 }
 
 /// This struct inserts into the table corresponding to [`TransmissionLocationRaw`]
@@ -103,6 +151,10 @@ pub struct InsertTransmissionLocationRaw {
     pub trekkie_run: uuid::Uuid,
     /// User, from whose trekkie run this undeduped location was inferred
     pub run_owner: uuid::Uuid,
+    /// Accuracy in meters of the gps fix this location was interpolated from
+    pub accuracy: Option<f64>,
+    /// Time in milliseconds between the gps fix and the telegram
+    pub time_offset: Option<i64>,
 }
 ///
 /// The transmission location that get sent out as part of [`LocationsJson`] from datacare API
@@ -126,6 +178,7 @@ pub struct LocationsJson {
 }
 
 /// Error for associated functions and methods over [`TransmissionLocation`] struct
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum TransmissionLocaionError {
     /// Input provided was empty
     EmptyInput,
@@ -138,6 +191,28 @@ pub enum TransmissionLocaionError {
     NoMatches,
 }
 
+impl From<&TransmissionLocation> for ApiTransmissionLocation {
+    /// Exposes how trustworthy the location is in the properties: `ground_truth`, `uncertainty`
+    /// and `spread` in meters, `sample_count`, `run_count`, `owner_count` and `last_updated`.
+    fn from(location: &TransmissionLocation) -> Self {
+        ApiTransmissionLocation {
+            lat: location.lat,
+            lon: location.lon,
+            properties: serde_json::json!({
+                "ground_truth": location.ground_truth,
+                "uncertainty": location.uncertainty,
+                "sample_count": location.sample_count,
+                "spread": location.spread,
+                "run_count": location.run_count,
+                "owner_count": location.owner_count,
+                "last_updated": location
+                    .last_updated
+                    .map(|time| time.and_utc().to_rfc3339()),
+            }),
+        }
+    }
+}
+
 impl ApiTransmissionLocation {
     /// Updates property field with epsg3857 coordinates, calculated from `loc` and `lon` fileds of
     /// the struct. If field doesn't exist, creates it.
@@ -159,58 +234,323 @@ impl ApiTransmissionLocation {
     }
 }
 
-type TransmissionLocationResult = Result<InsertTransmissionLocation, TransmissionLocaionError>;
-impl InsertTransmissionLocation {
-    /// Maximum distance at which the raw point is considered to be corresponding to the report
-    /// location cluster
-    // TODO: Should we make this configurable?
-    pub const MAX_SANE_DISTANCE: f64 = 50_f64;
+/// Error returned by [`LocationsJson::from_geojson`].
+#[derive(Debug)]
+pub enum LocationsGeoJsonError {
+    /// See [`serde_json::Error`]
+    SerdeJsonError(serde_json::Error),
+    /// The GeoJSON was written with a different [`SCHEMA`] version, contains the version found.
+    SchemaMismatch(String),
+    /// A required member is missing.
+    Missing(&'static str),
+    /// A feature id is not a reporting point.
+    InvalidReportingPoint(serde_json::Value),
+}
 
-    /// This function filters out outliers for specific reporting_point
-    fn filter_outliers(
-        input: Vec<TransmissionLocationRaw>,
-    ) -> Result<Vec<TransmissionLocationRaw>, TransmissionLocaionError> {
-        // Edge case
-        if input.is_empty() {
-            return Err(TransmissionLocaionError::EmptyInput);
+impl std::fmt::Display for LocationsGeoJsonError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LocationsGeoJsonError::SerdeJsonError(e) => write!(f, "{e}"),
+            LocationsGeoJsonError::SchemaMismatch(found) => {
+                write!(f, "expected schema {SCHEMA}, found {found}")
+            }
+            LocationsGeoJsonError::Missing(member) => write!(f, "missing {member}"),
+            LocationsGeoJsonError::InvalidReportingPoint(id) => {
+                write!(f, "invalid reporting point: {id}")
+            }
         }
+    }
+}
 
-        let (lats, lons): (Vec<f64>, Vec<f64>) = input.iter().map(|s| (s.lat, s.lon)).unzip();
+impl std::error::Error for LocationsGeoJsonError {}
 
-        let center_point: (f64, f64) = (
-            lats.iter().sum::<f64>() / (lats.len() as f64),
-            lons.iter().sum::<f64>() / (lons.len() as f64),
-        );
+impl From<serde_json::Error> for LocationsGeoJsonError {
+    fn from(e: serde_json::Error) -> LocationsGeoJsonError {
+        LocationsGeoJsonError::SerdeJsonError(e)
+    }
+}
 
-        let mut filtered: Vec<TransmissionLocationRaw> = Vec::new();
+impl LocationsJson {
+    /// Converts the locations into a GeoJSON `FeatureCollection` with a `Point` feature per
+    /// transmission location. The reporting point is the id of the feature and is added to its
+    /// properties. The region and the [`SCHEMA`] version are stored in the `region` and `schema`
+    /// members of the collection.
+    pub fn to_geojson(&self) -> serde_json::Value {
+        let mut reporting_points: Vec<&i64> = self.transmission_locations.keys().collect();
+        reporting_points.sort_unstable();
 
-        for i in input {
-            if i.distance_from(center_point) < SANE_INTERPOLATION_DISTANCE as f64 {
-                filtered.push(i)
-            }
+        let features: Vec<serde_json::Value> = reporting_points
+            .into_iter()
+            .map(|reporting_point| {
+                let location = &self.transmission_locations[reporting_point];
+                let mut properties = match &location.properties {
+                    serde_json::Value::Object(properties) => properties.clone(),
+                    _ => serde_json::Map::new(),
+                };
+                properties.insert(
+                    String::from("reporting_point"),
+                    serde_json::Value::from(*reporting_point),
+                );
+
+                serde_json::json!({
+                    "type": "Feature",
+                    "id": reporting_point,
+                    "geometry": {
+                        "type": "Point",
+                        "coordinates": [location.lon, location.lat],
+                    },
+                    "properties": properties,
+                })
+            })
+            .collect();
+
+        serde_json::json!({
+            "type": "FeatureCollection",
+            "schema": SCHEMA,
+            "region": self.region,
+            "features": features,
+        })
+    }
+
+    /// Reads locations written by [`LocationsJson::to_geojson`]. Fails if the GeoJSON was
+    /// written with a different [`SCHEMA`] version. Properties which are not an object are read
+    /// back as an empty object.
+    pub fn from_geojson(geojson: &serde_json::Value) -> Result<Self, LocationsGeoJsonError> {
+        let schema = geojson["schema"]
+            .as_str()
+            .ok_or(LocationsGeoJsonError::Missing("schema"))?;
+        if schema != SCHEMA {
+            return Err(LocationsGeoJsonError::SchemaMismatch(schema.to_string()));
         }
 
-        if filtered.is_empty() {
-            return Err(TransmissionLocaionError::NoMatches);
+        let region = serde_json::from_value(geojson["region"].clone())?;
+        let features = geojson["features"]
+            .as_array()
+            .ok_or(LocationsGeoJsonError::Missing("features"))?;
+
+        let mut transmission_locations = HashMap::new();
+        for feature in features {
+            let reporting_point = feature["id"].as_i64().ok_or_else(|| {
+                LocationsGeoJsonError::InvalidReportingPoint(feature["id"].clone())
+            })?;
+            let coordinate = |index: usize| {
+                feature["geometry"]["coordinates"][index]
+                    .as_f64()
+                    .ok_or(LocationsGeoJsonError::Missing("coordinates"))
+            };
+
+            let mut properties = match &feature["properties"] {
+                serde_json::Value::Object(properties) => properties.clone(),
+                _ => serde_json::Map::new(),
+            };
+            properties.remove("reporting_point");
+
+            transmission_locations.insert(
+                reporting_point,
+                ApiTransmissionLocation {
+                    lat: coordinate(1)?,
+                    lon: coordinate(0)?,
+                    properties: serde_json::Value::Object(properties),
+                },
+            );
+        }
+
+        Ok(LocationsJson {
+            region,
+            transmission_locations,
+        })
+    }
+}
+
+/// Settings for inferring transmission locations from raw measurements. Regions can override the
+/// interpolation distance, so dense inner-city junctions and sparse suburban lines can use
+/// different radii.
+///
+/// Raw locations are weighted by the inverse of their variance. The standard deviation of a raw
+/// location is the accuracy of its gps fix combined with the distance a vehicle travels at
+/// `time_offset_speed` between the fix and the telegram.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LocationInferenceConfig {
+    /// maximum distance in meters between a raw position and the transmission location it
+    /// belongs to
+    pub interpolation_distance: f64,
+    /// interpolation distance in meters for specific regions
+    pub region_interpolation_distances: HashMap<i64, f64>,
+    /// accuracy in meters assumed for raw locations without one
+    pub default_accuracy: f64,
+    /// speed in meters per second at which the uncertainty grows with the time offset
+    pub time_offset_speed: f64,
+}
+
+impl Default for LocationInferenceConfig {
+    fn default() -> Self {
+        LocationInferenceConfig {
+            interpolation_distance: DEFAULT_INTERPOLATION_DISTANCE,
+            region_interpolation_distances: HashMap::new(),
+            default_accuracy: DEFAULT_GPS_ACCURACY,
+            time_offset_speed: DEFAULT_TIME_OFFSET_SPEED,
         }
+    }
+}
+
+impl LocationInferenceConfig {
+    /// Overrides the interpolation distance for `region`.
+    pub fn with_region(mut self, region: i64, interpolation_distance: f64) -> Self {
+        self.region_interpolation_distances
+            .insert(region, interpolation_distance);
+        self
+    }
+
+    /// Returns the interpolation distance in meters used for `region`.
+    pub fn get_interpolation_distance(&self, region: i64) -> f64 {
+        self.region_interpolation_distances
+            .get(&region)
+            .copied()
+            .unwrap_or(self.interpolation_distance)
+    }
 
-        Ok(filtered)
+    /// Returns the weight of the raw location, the inverse of its variance in square meters. The
+    /// standard deviation is at least one meter.
+    pub fn get_weight(&self, raw: &TransmissionLocationRaw) -> f64 {
+        let accuracy = raw
+            .accuracy
+            .filter(|accuracy| accuracy.is_finite() && *accuracy >= 0_f64)
+            .unwrap_or(self.default_accuracy);
+        let drift = raw.time_offset.unwrap_or_default().unsigned_abs() as f64 / 1000_f64
+            * self.time_offset_speed;
+
+        1_f64 / accuracy.hypot(drift).max(1_f64).powi(2)
     }
+}
+
+type TransmissionLocationResult = Result<InsertTransmissionLocation, TransmissionLocaionError>;
+impl InsertTransmissionLocation {
+    /// Maximum distance at which the raw point is considered to be corresponding to the report
+    /// location cluster
+    #[deprecated(
+        since = "0.10.0",
+        note = "Please use LocationInferenceConfig from now on!"
+    )]
+    pub const MAX_SANE_DISTANCE: f64 = 50_f64;
 
     /// This function creates the [`InsertTransmissionLocation`] from the vector of raw
-    /// transmission locations. Points are averaged, any outliers further away then
-    /// [`MAX_SANE_DISTANCE`] are discarded. All the [`TransmissionLocationRaw`] should have the
-    /// same region, or the function will fail.
+    /// transmission locations. Any outliers further away from the mean then the interpolation
+    /// distance `config` sets for the region are discarded, the remaining points are averaged
+    /// weighted by their accuracy. The uncertainty is the standard error of the weighted mean.
+    /// All the [`TransmissionLocationRaw`] should have the same region, or the function will fail.
     ///
     /// **This is default way for updating the [`TransmissionLocation`]**. The analysis should be
     /// performed on the whole set of raw locations, to prevent biasing the data.
-    pub fn try_from_raw(raw: Vec<TransmissionLocationRaw>) -> TransmissionLocationResult {
+    pub fn try_from_raw(
+        raw: Vec<TransmissionLocationRaw>,
+        config: &LocationInferenceConfig,
+    ) -> TransmissionLocationResult {
+        let (region, reporting_point) = Self::check_raw(&raw)?;
+
+        let filter = MeanFilter {
+            radius: config.get_interpolation_distance(region),
+        };
+        let points: Vec<(f64, f64)> = raw.iter().map(|loc| (loc.lat, loc.lon)).collect();
+        let inliers = filter.inliers(&points);
+        if inliers.is_empty() {
+            return Err(TransmissionLocaionError::NoMatches);
+        }
+
+        let inliers: Vec<&TransmissionLocationRaw> =
+            inliers.into_iter().map(|index| &raw[index]).collect();
+        let (mut lat, mut lon, mut weights) = (0_f64, 0_f64, 0_f64);
+        for loc in &inliers {
+            let weight = config.get_weight(loc);
+            lat += loc.lat * weight;
+            lon += loc.lon * weight;
+            weights += weight;
+        }
+
+        let mut location = Self::with_provenance(
+            region,
+            reporting_point,
+            (lat / weights, lon / weights),
+            &inliers,
+        );
+        location.uncertainty = Some((1_f64 / weights).sqrt());
+        Ok(location)
+    }
+
+    /// Same as [`InsertTransmissionLocation::try_from_raw`] but the position is calculated by
+    /// `estimator`, see [`estimator`] for the available strategies. The accuracy of the raw
+    /// locations is ignored and no uncertainty is calculated. The provenance only covers the raw
+    /// locations the estimator didn't consider outliers.
+    pub fn try_from_raw_with(
+        raw: Vec<TransmissionLocationRaw>,
+        estimator: &dyn LocationEstimator,
+    ) -> TransmissionLocationResult {
+        let (region, reporting_point) = Self::check_raw(&raw)?;
+
+        let points: Vec<(f64, f64)> = raw.iter().map(|loc| (loc.lat, loc.lon)).collect();
+        let estimate = estimator.estimate(&points)?;
+
+        let inliers: Vec<&TransmissionLocationRaw> =
+            estimate.inliers.iter().map(|index| &raw[*index]).collect();
+        Ok(Self::with_provenance(
+            region,
+            reporting_point,
+            estimate.position,
+            &inliers,
+        ))
+    }
+
+    /// Creates the location at `position` inferred from `raw` and fills in the sample count,
+    /// spread, run and owner count.
+    fn with_provenance(
+        region: i64,
+        reporting_point: i32,
+        (lat, lon): (f64, f64),
+        raw: &[&TransmissionLocationRaw],
+    ) -> InsertTransmissionLocation {
+        let count = |n: usize| i32::try_from(n).unwrap_or(i32::MAX);
+        let spread = (!raw.is_empty()).then(|| {
+            let squares: f64 = raw
+                .iter()
+                .map(|loc| loc.distance_from((lat, lon)).powi(2))
+                .sum();
+            (squares / raw.len() as f64).sqrt()
+        });
+
+        InsertTransmissionLocation {
+            id: None,
+            region,
+            reporting_point,
+            lat,
+            lon,
+            ground_truth: false,
+            uncertainty: None,
+            sample_count: count(raw.len()),
+            spread,
+            run_count: count(
+                raw.iter()
+                    .map(|loc| loc.trekkie_run)
+                    .collect::<HashSet<_>>()
+                    .len(),
+            ),
+            owner_count: count(
+                raw.iter()
+                    .map(|loc| loc.run_owner)
+                    .collect::<HashSet<_>>()
+                    .len(),
+            ),
+            last_updated: Some(Utc::now().naive_utc()),
+        }
+    }
+
+    /// Returns region and reporting point shared by all raw locations.
+    fn check_raw(raw: &[TransmissionLocationRaw]) -> Result<(i64, i32), TransmissionLocaionError> {
         if raw.is_empty() {
             return Err(TransmissionLocaionError::EmptyInput);
         }
         let region = raw[0].region;
         let reporting_point = raw[0].reporting_point;
-        for loc in &raw {
+        for loc in raw {
             if loc.region != region {
                 return Err(TransmissionLocaionError::RegionMismatch);
             }
@@ -219,22 +559,7 @@ impl InsertTransmissionLocation {
             }
         }
 
-        let filtered = Self::filter_outliers(raw)?;
-
-        let (lats, lons): (Vec<f64>, Vec<f64>) = filtered.iter().map(|v| (v.lat, v.lon)).unzip();
-        let (lat, lon): (f64, f64) = (
-            lats.iter().sum::<f64>() / (lats.len() as f64),
-            lons.iter().sum::<f64>() / (lons.len() as f64),
-        );
-
-        Ok(InsertTransmissionLocation {
-            id: None,
-            region,
-            reporting_point,
-            lat,
-            lon,
-            ground_truth: false,
-        })
+        Ok((region, reporting_point))
     }
 }
 
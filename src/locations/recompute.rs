@@ -0,0 +1,141 @@
+//!
+//! Recomputes the transmission locations of a region from its raw transmission locations. Ground
+//! truth locations were inserted from absolute data, they are never touched by the inference.
+//!
+
+use super::{
+    InsertTransmissionLocation, LocationInferenceConfig, REGION_POSITION_UNIQUE_CONSTRAINT,
+    TransmissionLocaionError, TransmissionLocation, TransmissionLocationRaw,
+};
+use crate::schema::{r09_transmission_locations, r09_transmission_locations_raw};
+
+use diesel::pg::upsert::on_constraint;
+use diesel::upsert::excluded;
+use diesel::{Connection, ExpressionMethods, PgConnection, QueryDsl, RunQueryDsl};
+
+use std::collections::{BTreeMap, HashMap};
+
+/// What [`recompute_region`] changed, every list holds reporting points in ascending order.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RecomputeReport {
+    /// reporting points which got a transmission location for the first time
+    pub inserted: Vec<i32>,
+    /// reporting points whose transmission location was replaced
+    pub updated: Vec<i32>,
+    /// reporting points with raw locations that were skipped because of a ground truth location
+    pub ground_truth: Vec<i32>,
+    /// reporting points whose location couldn't be inferred, their stored location is kept
+    pub failed: Vec<(i32, TransmissionLocaionError)>,
+}
+
+impl RecomputeReport {
+    /// Returns true if no transmission location was inserted or updated.
+    pub fn is_unchanged(&self) -> bool {
+        self.inserted.is_empty() && self.updated.is_empty()
+    }
+}
+
+/// Infers the transmission locations of all reporting points in `raw` with
+/// [`InsertTransmissionLocation::try_from_raw`] without touching the database. Reporting points
+/// with a ground truth location in `existing` are skipped. Returns the locations to upsert
+/// together with the report of what they change.
+pub fn plan_recompute(
+    existing: &[TransmissionLocation],
+    raw: Vec<TransmissionLocationRaw>,
+    config: &LocationInferenceConfig,
+) -> (Vec<InsertTransmissionLocation>, RecomputeReport) {
+    let existing: HashMap<(i64, i32), &TransmissionLocation> = existing
+        .iter()
+        .map(|location| ((location.region, location.reporting_point), location))
+        .collect();
+
+    let mut grouped: BTreeMap<(i64, i32), Vec<TransmissionLocationRaw>> = BTreeMap::new();
+    for loc in raw {
+        grouped
+            .entry((loc.region, loc.reporting_point))
+            .or_default()
+            .push(loc);
+    }
+
+    let mut locations = Vec::new();
+    let mut report = RecomputeReport::default();
+    for ((region, reporting_point), raw) in grouped {
+        let current = existing.get(&(region, reporting_point));
+        if current.is_some_and(|location| location.ground_truth) {
+            report.ground_truth.push(reporting_point);
+            continue;
+        }
+
+        match InsertTransmissionLocation::try_from_raw(raw, config) {
+            Ok(location) => {
+                if current.is_some() {
+                    report.updated.push(reporting_point);
+                } else {
+                    report.inserted.push(reporting_point);
+                }
+                locations.push(location);
+            }
+            Err(error) => report.failed.push((reporting_point, error)),
+        }
+    }
+
+    (locations, report)
+}
+
+/// Recomputes the transmission locations of `region` from all its raw transmission locations in
+/// a single transaction, see [`plan_recompute`]. Locations are upserted on
+/// [`REGION_POSITION_UNIQUE_CONSTRAINT`], an upsert never overwrites a ground truth location even
+/// if it was inserted concurrently.
+pub fn recompute_region(
+    region: i64,
+    config: &LocationInferenceConfig,
+    database_connection: &mut PgConnection,
+) -> Result<RecomputeReport, diesel::result::Error> {
+    use crate::schema::r09_transmission_locations::dsl as locations;
+
+    database_connection.transaction(|connection| {
+        let existing = r09_transmission_locations::table
+            .filter(locations::region.eq(region))
+            .load::<TransmissionLocation>(connection)?;
+        let raw = r09_transmission_locations_raw::table
+            .filter(r09_transmission_locations_raw::region.eq(region))
+            .load::<TransmissionLocationRaw>(connection)?;
+
+        let (upserts, mut report) = plan_recompute(&existing, raw, config);
+        for location in upserts {
+            let upsert = diesel::insert_into(r09_transmission_locations::table)
+                .values(&location)
+                .on_conflict(on_constraint(REGION_POSITION_UNIQUE_CONSTRAINT))
+                .do_update()
+                .set((
+                    locations::lat.eq(excluded(locations::lat)),
+                    locations::lon.eq(excluded(locations::lon)),
+                    locations::uncertainty.eq(excluded(locations::uncertainty)),
+                    locations::sample_count.eq(excluded(locations::sample_count)),
+                    locations::spread.eq(excluded(locations::spread)),
+                    locations::run_count.eq(excluded(locations::run_count)),
+                    locations::owner_count.eq(excluded(locations::owner_count)),
+                    locations::last_updated.eq(excluded(locations::last_updated)),
+                ));
+            let changed = diesel::query_dsl::methods::FilterDsl::filter(
+                upsert,
+                locations::ground_truth.eq(false),
+            )
+            .execute(connection)?;
+
+            if changed == 0 {
+                // became ground truth after the existing locations were loaded
+                report
+                    .inserted
+                    .retain(|point| *point != location.reporting_point);
+                report
+                    .updated
+                    .retain(|point| *point != location.reporting_point);
+                report.ground_truth.push(location.reporting_point);
+            }
+        }
+        report.ground_truth.sort_unstable();
+
+        Ok(report)
+    })
+}
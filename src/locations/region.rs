@@ -19,6 +19,10 @@ pub const REGION_CACHE_EXPIRATION: i64 = 24 * 60 * 60;
 /// Name for a cache file
 pub const REGION_CACHE_FILE: &str = "region_cache.json";
 /// maximum distance in meters
+#[deprecated(
+    since = "0.10.0",
+    note = "Please use LocationInferenceConfig from now on!"
+)]
 pub const SANE_INTERPOLATION_DISTANCE: i32 = 50;
 
 /// Struct holding the information for a region.
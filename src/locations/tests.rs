@@ -1,25 +1,737 @@
+use super::*;
+
 #[test]
 fn test_serialization() {
-    let data = TransmissionPosition {
-        dhid: Some("dhid".to_string()),
-        name: Some("name".to_string()),
-        telegram_type: TelegramType::DoorClosed,
-        direction: 0,
+    let data = ApiTransmissionLocation {
         lat: 0.0,
         lon: 0.0,
+        properties: serde_json::json!({ "name": "name" }),
     };
 
     let reference = String::from(
         "{
-  \"dhid\": \"dhid\",
-  \"name\": \"name\",
-  \"telegram_type\": \"3\",
-  \"direction\": 0,
   \"lat\": 0.0,
-  \"lon\": 0.0
+  \"lon\": 0.0,
+  \"properties\": {
+    \"name\": \"name\"
+  }
 }",
     );
     let json_data = serde_json::to_string_pretty(&data).expect("cannot serialize structs!");
 
     assert_eq!(json_data, reference);
 }
+
+fn r09_telegram() -> crate::telegrams::r09::R09SaveTelegram {
+    crate::telegrams::r09::R09SaveTelegram {
+        id: Some(42),
+        time: chrono::DateTime::from_timestamp_millis(1_681_000_000_123)
+            .unwrap()
+            .naive_utc(),
+        station: uuid::Uuid::nil(),
+        r09_type: crate::telegrams::r09::R09Type::R16,
+        delay: Some(-2),
+        reporting_point: 0x1a2b,
+        junction: 0x1a2,
+        direction: 0,
+        request_status: 1,
+        priority: None,
+        direction_request: None,
+        line: Some(85),
+        run_number: Some(12),
+        destination_number: Some(304),
+        train_length: None,
+        vehicle_number: None,
+        operator: None,
+        region: 1,
+    }
+}
+
+#[test]
+fn test_waypoint_from_r09() {
+    use waypoint::{Waypoint, WaypointError};
+
+    let telegram = r09_telegram();
+    let location = TransmissionLocation {
+        id: 1,
+        region: 1,
+        reporting_point: 0x1a2b,
+        lat: 51.05,
+        lon: 13.73,
+        ground_truth: true,
+        uncertainty: None,
+        sample_count: 0,
+        spread: None,
+        run_count: 0,
+        owner_count: 0,
+        last_updated: None,
+    };
+
+    let waypoint = Waypoint::from_r09(&telegram, &location).expect("cannot create waypoint!");
+    assert_eq!(waypoint.id, 42);
+    assert_eq!(waypoint.time.as_millis(), 1_681_000_000_123);
+    assert_eq!((waypoint.lat, waypoint.lon), (51.05, 13.73));
+    assert_eq!((waypoint.line, waypoint.run), (85, 12));
+    assert_eq!(waypoint.delayed, Some(-2.0));
+    assert_eq!(waypoint.r09_reporting_point, Some(0x1a2b));
+    assert_eq!(waypoint.r09_destination_number, Some(304));
+
+    let other = TransmissionLocation {
+        reporting_point: 0x1a2c,
+        ..location.clone()
+    };
+    assert_eq!(
+        Waypoint::from_r09(&telegram, &other).err(),
+        Some(WaypointError::ReportingPointMismatch {
+            telegram: 0x1a2b,
+            location: 0x1a2c
+        })
+    );
+
+    let mut without_line = telegram.clone();
+    without_line.line = None;
+    assert_eq!(
+        Waypoint::from_r09(&without_line, &location).err(),
+        Some(WaypointError::MissingLine)
+    );
+}
+
+fn dresden() -> region::Region {
+    region::Region {
+        id: 1,
+        name: "Dresden".to_string(),
+        transport_company: "DVB".to_string(),
+        regional_company: None,
+        frequency: None,
+        r09_type: None,
+        encoding: None,
+        deactivated: false,
+        lat: 51.05,
+        lon: 13.74,
+        zoom: 12.0,
+        work_in_progress: false,
+    }
+}
+
+#[test]
+fn test_waypoint_from_r09_locations() {
+    use waypoint::{Waypoint, WaypointError};
+
+    let mut locations = LocationsJson {
+        region: dresden(),
+        transmission_locations: HashMap::new(),
+    };
+    let mut telegram = r09_telegram();
+
+    assert_eq!(
+        Waypoint::from_r09_locations(&telegram, &locations).err(),
+        Some(WaypointError::UnknownReportingPoint {
+            region: 1,
+            reporting_point: 0x1a2b
+        })
+    );
+
+    locations.transmission_locations.insert(
+        0x1a2b,
+        ApiTransmissionLocation {
+            lat: 51.06,
+            lon: 13.72,
+            properties: serde_json::Value::Null,
+        },
+    );
+    let waypoint =
+        Waypoint::from_r09_locations(&telegram, &locations).expect("cannot create waypoint!");
+    assert_eq!((waypoint.lat, waypoint.lon), (51.06, 13.72));
+
+    telegram.region = 2;
+    assert_eq!(
+        Waypoint::from_r09_locations(&telegram, &locations).err(),
+        Some(WaypointError::RegionMismatch {
+            telegram: 2,
+            location: 1
+        })
+    );
+}
+
+#[cfg(feature = "trekkie")]
+#[test]
+fn test_waypoint_from_gps() {
+    use crate::grpc::GrpcWaypoint;
+    use crate::trekkie::TrekkieRun;
+    use waypoint::{WayPointType, Waypoint, WaypointError};
+
+    let timestamp = chrono::DateTime::from_timestamp_millis(1_681_000_000_123)
+        .unwrap()
+        .naive_utc();
+    let run = TrekkieRun {
+        start_time: timestamp,
+        end_time: timestamp,
+        line: 85,
+        run: 12,
+        region: 1,
+        owner: uuid::Uuid::nil(),
+        finished: true,
+        id: uuid::Uuid::from_u128(1),
+        correlated: false,
+        app_commit: String::new(),
+        app_name: String::new(),
+    };
+    let point = gps::GpsPoint {
+        id: 7,
+        trekkie_run: run.id,
+        timestamp,
+        lat: 51.05,
+        lon: 13.73,
+        elevation: None,
+        accuracy: Some(4.0),
+        vertical_accuracy: None,
+        bearing: None,
+        speed: None,
+    };
+
+    let waypoint = Waypoint::from_gps(&point, &run).expect("cannot create waypoint!");
+    assert!(matches!(waypoint.source, WayPointType::TrekkieGPS));
+    assert_eq!(waypoint.id, 7);
+    assert_eq!(waypoint.time.as_millis(), 1_681_000_000_123);
+    assert_eq!((waypoint.line, waypoint.run, waypoint.region), (85, 12, 1));
+
+    let grpc = GrpcWaypoint::try_from(waypoint.clone()).expect("cannot convert waypoint!");
+    assert_eq!(grpc.source, WayPointType::TrekkieGPS as i32);
+    assert_eq!((grpc.lat, grpc.lon), (51.05, 13.73));
+    assert_eq!(grpc.time, 1_681_000_000_123);
+    let waypoint = Waypoint::try_from(grpc).expect("cannot convert waypoint!");
+    assert!(matches!(waypoint.source, WayPointType::TrekkieGPS));
+    assert_eq!(waypoint.id, 7);
+
+    let before_epoch = Waypoint {
+        time: crate::timestamp::Timestamp::try_from(-1_i64).unwrap(),
+        ..waypoint
+    };
+    assert_eq!(
+        GrpcWaypoint::try_from(before_epoch.clone()).err(),
+        Some(WaypointError::TimeBeforeEpoch(before_epoch.time))
+    );
+
+    let other = TrekkieRun {
+        id: uuid::Uuid::from_u128(2),
+        ..run
+    };
+    assert_eq!(
+        Waypoint::from_gps(&point, &other).err(),
+        Some(WaypointError::RunMismatch {
+            point: uuid::Uuid::from_u128(1),
+            run: uuid::Uuid::from_u128(2)
+        })
+    );
+}
+
+#[cfg(feature = "trekkie")]
+#[test]
+fn test_gps_point_grpc_round_trip() {
+    use crate::grpc::GrpcGpsPoint;
+    use crate::trekkie::TrekkieRun;
+    use gps::{GpsConversionError, InsertGpsPoint};
+
+    let timestamp = chrono::DateTime::from_timestamp_millis(1_681_000_000_123)
+        .unwrap()
+        .naive_utc();
+    let run = TrekkieRun {
+        start_time: timestamp,
+        end_time: timestamp,
+        line: 85,
+        run: 12,
+        region: 1,
+        owner: uuid::Uuid::nil(),
+        finished: true,
+        id: uuid::Uuid::from_u128(1),
+        correlated: false,
+        app_commit: String::new(),
+        app_name: String::new(),
+    };
+    let point = GrpcGpsPoint {
+        time: 1_681_000_000_123,
+        id: 7,
+        region: 1,
+        lat: 51.05,
+        lon: 13.73,
+        line: 85,
+        run: 12,
+        elevation: Some(112.5),
+        accuracy: Some(4.0),
+        vertical_accuracy: Some(8.0),
+        bearing: Some(270.0),
+        speed: Some(11.2),
+        trekkie_run: run.id.to_string(),
+    };
+
+    let insert = InsertGpsPoint::try_from(point.clone()).expect("cannot convert gps point!");
+    assert_eq!(insert.id, Some(7));
+    assert_eq!(insert.timestamp, timestamp);
+    assert_eq!(insert.bearing, Some(270.0));
+    assert_eq!(
+        GrpcGpsPoint::try_from((insert, &run)).expect("cannot convert gps point!"),
+        point
+    );
+
+    let mut invalid = point.clone();
+    invalid.trekkie_run = "not a uuid".to_string();
+    assert!(matches!(
+        InsertGpsPoint::try_from(invalid),
+        Err(GpsConversionError::InvalidTrekkieRun(_))
+    ));
+
+    let mut invalid = point;
+    invalid.lat = 91.0;
+    assert!(matches!(
+        InsertGpsPoint::try_from(invalid),
+        Err(GpsConversionError::InvalidPosition(_))
+    ));
+
+    let other = TrekkieRun {
+        id: uuid::Uuid::from_u128(2),
+        ..run.clone()
+    };
+    assert!(matches!(
+        GrpcGpsPoint::try_from((insert, &other)),
+        Err(GpsConversionError::RunMismatch { .. })
+    ));
+
+    let before_epoch = InsertGpsPoint {
+        timestamp: chrono::DateTime::from_timestamp_millis(-1)
+            .unwrap()
+            .naive_utc(),
+        ..insert
+    };
+    assert_eq!(
+        GrpcGpsPoint::try_from((before_epoch, &run)).err(),
+        Some(GpsConversionError::TimeBeforeEpoch(before_epoch.timestamp))
+    );
+
+    // sub-millisecond precision is lost and an id of 0 means not set
+    let lossy = InsertGpsPoint {
+        id: Some(0),
+        timestamp: timestamp + chrono::Duration::microseconds(456),
+        ..insert
+    };
+    let sent = GrpcGpsPoint::try_from((lossy, &run)).expect("cannot convert gps point!");
+    assert_eq!(sent.time, 1_681_000_000_123);
+    assert_eq!(sent.id, 0);
+    let received = InsertGpsPoint::try_from(sent).expect("cannot convert gps point!");
+    assert_eq!(received.timestamp, timestamp);
+    assert_eq!(received.id, None);
+}
+
+/// Point `north` and `east` meters away from the reference position.
+fn offset((north, east): (f64, f64)) -> (f64, f64) {
+    const REFERENCE: (f64, f64) = (51.05, 13.73);
+    let meters_per_degree = MEAN_EARTH_RADIUS as f64 * std::f64::consts::PI / 180.0;
+
+    (
+        REFERENCE.0 + north / meters_per_degree,
+        REFERENCE.1 + east / (meters_per_degree * REFERENCE.0.to_radians().cos()),
+    )
+}
+
+/// `count` points scattered up to about 8 meters around the point `north` and `east` meters away
+/// from the reference position.
+fn cloud(count: usize, (north, east): (f64, f64)) -> Vec<(f64, f64)> {
+    (0..count)
+        .map(|i| {
+            let angle = i as f64 * 2.4;
+            let radius = (i % 6 + 1) as f64 * 1.3;
+            offset((north + radius * angle.sin(), east + radius * angle.cos()))
+        })
+        .collect()
+}
+
+/// Raw location of reporting point `0x1a2b` in region 1 at `(lat, lon)` without accuracy and
+/// time offset, recorded during the nil trekkie run.
+fn raw_location((lat, lon): (f64, f64)) -> TransmissionLocationRaw {
+    TransmissionLocationRaw {
+        id: 0,
+        region: 1,
+        reporting_point: 0x1a2b,
+        lat,
+        lon,
+        trekkie_run: uuid::Uuid::nil(),
+        run_owner: uuid::Uuid::nil(),
+        accuracy: None,
+        time_offset: None,
+    }
+}
+
+#[test]
+fn test_estimators_ignore_far_outliers() {
+    use estimator::*;
+
+    // a single bad run placed two points a kilometer away
+    let mut points = cloud(18, (0.0, 0.0));
+    points.extend(cloud(2, (1000.0, 0.0)));
+
+    assert_eq!(
+        MeanFilter::default().estimate(&points),
+        Err(TransmissionLocaionError::NoMatches)
+    );
+
+    let estimators: [&dyn LocationEstimator; 3] = [
+        &GeometricMedian::default(),
+        &SigmaClipping::default(),
+        &Dbscan::default(),
+    ];
+    for estimator in estimators {
+        let estimate = estimator
+            .estimate(&points)
+            .expect("cannot estimate location!");
+        assert!(estimate.position.distance_from(offset((0.0, 0.0))) < 3.0);
+        assert_eq!(estimate.inliers, (0..18).collect::<Vec<usize>>());
+    }
+}
+
+#[test]
+fn test_estimators_ignore_outlier_cluster() {
+    use estimator::*;
+
+    // a whole bad run 200 meters away, too many points for sigma clipping
+    let mut points = cloud(20, (0.0, 0.0));
+    points.extend(cloud(8, (0.0, 200.0)));
+
+    let estimators: [&dyn LocationEstimator; 2] = [&GeometricMedian::default(), &Dbscan::default()];
+    for estimator in estimators {
+        let estimate = estimator
+            .estimate(&points)
+            .expect("cannot estimate location!");
+        assert!(estimate.position.distance_from(offset((0.0, 0.0))) < 3.0);
+    }
+
+    // scattered points don't form a cluster
+    let scattered: Vec<(f64, f64)> = (0..5).map(|i| offset((i as f64 * 100.0, 0.0))).collect();
+    assert_eq!(
+        Dbscan::default().estimate(&scattered),
+        Err(TransmissionLocaionError::NoMatches)
+    );
+    assert_eq!(
+        GeometricMedian::default().estimate(&[]),
+        Err(TransmissionLocaionError::EmptyInput)
+    );
+}
+
+#[test]
+fn test_try_from_raw_with_estimator() {
+    use estimator::{GeometricMedian, MeanFilter};
+
+    let mut points = cloud(9, (0.0, 0.0));
+    points.push(offset((0.0, 30.0)));
+    let raw: Vec<TransmissionLocationRaw> = points
+        .iter()
+        .enumerate()
+        .map(|(id, position)| TransmissionLocationRaw {
+            id: id as i64,
+            ..raw_location(*position)
+        })
+        .collect();
+
+    // the mean filter keeps the point 30 meters away and averages it in
+    let mean =
+        InsertTransmissionLocation::try_from_raw(raw.clone(), &LocationInferenceConfig::default())
+            .unwrap();
+    let with_mean =
+        InsertTransmissionLocation::try_from_raw_with(raw.clone(), &MeanFilter::default()).unwrap();
+    // without accuracies all raw locations have the same weight
+    assert!((mean.lat, mean.lon).distance_from((with_mean.lat, with_mean.lon)) < 1e-6);
+    assert_eq!(with_mean.uncertainty, None);
+    assert!((mean.lat, mean.lon).distance_from(offset((0.0, 3.0))) < 1.0);
+
+    let median =
+        InsertTransmissionLocation::try_from_raw_with(raw, &GeometricMedian::default()).unwrap();
+    assert_eq!((median.region, median.reporting_point), (1, 0x1a2b));
+    assert!((median.lat, median.lon).distance_from(offset((0.0, 0.0))) < 2.0);
+}
+
+#[test]
+fn test_location_inference_config() {
+    let config = LocationInferenceConfig::default().with_region(1, 10.0);
+    assert_eq!(config.get_interpolation_distance(1), 10.0);
+    assert_eq!(
+        config.get_interpolation_distance(2),
+        DEFAULT_INTERPOLATION_DISTANCE
+    );
+
+    let config: LocationInferenceConfig =
+        serde_json::from_str("{\"region_interpolation_distances\": {\"3\": 120.0}}")
+            .expect("cannot deserialize config!");
+    assert_eq!(config.get_interpolation_distance(3), 120.0);
+    assert_eq!(
+        config.interpolation_distance,
+        DEFAULT_INTERPOLATION_DISTANCE
+    );
+
+    // a point 30 meters away is an outlier with the tighter radius of region 1
+    let mut points = cloud(9, (0.0, 0.0));
+    points.push(offset((0.0, 30.0)));
+    let raw = |region: i64| -> Vec<TransmissionLocationRaw> {
+        points
+            .iter()
+            .map(|position| TransmissionLocationRaw {
+                region,
+                ..raw_location(*position)
+            })
+            .collect()
+    };
+    let config = LocationInferenceConfig::default().with_region(1, 15.0);
+
+    let tight = InsertTransmissionLocation::try_from_raw(raw(1), &config).unwrap();
+    assert!((tight.lat, tight.lon).distance_from(offset((0.0, 0.0))) < 1.0);
+    let wide = InsertTransmissionLocation::try_from_raw(raw(2), &config).unwrap();
+    assert!((wide.lat, wide.lon).distance_from(offset((0.0, 3.0))) < 1.0);
+}
+
+#[test]
+fn test_try_from_raw_weighted() {
+    let config = LocationInferenceConfig::default();
+    let raw_with = |position: (f64, f64), accuracy: Option<f64>, time_offset: Option<i64>| {
+        TransmissionLocationRaw {
+            accuracy,
+            time_offset,
+            ..raw_location(offset(position))
+        }
+    };
+
+    // weights are the inverse variance, the time offset adds 10 meters per second
+    assert_eq!(
+        config.get_weight(&raw_with((0.0, 0.0), Some(4.0), None)),
+        1.0 / 16.0
+    );
+    assert_eq!(
+        config.get_weight(&raw_with((0.0, 0.0), None, None)),
+        1.0 / 100.0
+    );
+    assert_eq!(
+        config.get_weight(&raw_with((0.0, 0.0), Some(3.0), Some(-400))),
+        1.0 / 25.0
+    );
+    assert_eq!(
+        config.get_weight(&raw_with((0.0, 0.0), Some(0.0), None)),
+        1.0
+    );
+
+    // the accurate fix pulls the location towards it
+    let location = InsertTransmissionLocation::try_from_raw(
+        vec![
+            raw_with((0.0, 0.0), Some(2.0), Some(0)),
+            raw_with((0.0, 20.0), Some(20.0), None),
+            raw_with((0.0, 20.0), None, Some(2000)),
+        ],
+        &config,
+    )
+    .unwrap();
+    // weights 1/4, 1/400 and 1/500
+    let expected_east =
+        20.0 * (1.0 / 400.0 + 1.0 / 500.0) / (1.0 / 4.0 + 1.0 / 400.0 + 1.0 / 500.0);
+    assert!((location.lat, location.lon).distance_from(offset((0.0, expected_east))) < 0.01);
+
+    let uncertainty = location.uncertainty.expect("uncertainty missing!");
+    let expected_uncertainty = (1.0_f64 / (1.0 / 4.0 + 1.0 / 400.0 + 1.0 / 500.0)).sqrt();
+    assert!((uncertainty - expected_uncertainty).abs() < 1e-9);
+    assert!(uncertainty < 2.0);
+}
+
+#[test]
+fn test_try_from_raw_provenance() {
+    let runs = [uuid::Uuid::from_u128(1), uuid::Uuid::from_u128(2)];
+    let owner = uuid::Uuid::from_u128(3);
+    let mut raw: Vec<TransmissionLocationRaw> = [(0.0, 3.0), (0.0, -3.0), (4.0, 0.0), (-4.0, 0.0)]
+        .into_iter()
+        .enumerate()
+        .map(|(index, position)| TransmissionLocationRaw {
+            id: index as i64,
+            trekkie_run: runs[index % 2],
+            run_owner: owner,
+            accuracy: Some(5.0),
+            ..raw_location(offset(position))
+        })
+        .collect();
+    // an outlier of a third run by another user doesn't count
+    raw.push(TransmissionLocationRaw {
+        id: 4,
+        trekkie_run: uuid::Uuid::from_u128(4),
+        run_owner: uuid::Uuid::from_u128(5),
+        ..raw[0].clone()
+    });
+    (raw[4].lat, raw[4].lon) = offset((150.0, 0.0));
+
+    let location =
+        InsertTransmissionLocation::try_from_raw(raw.clone(), &LocationInferenceConfig::default())
+            .unwrap();
+    assert_eq!(location.sample_count, 4);
+    assert_eq!((location.run_count, location.owner_count), (2, 1));
+    // distances of 3, 3, 4 and 4 meters from the center
+    let spread = location.spread.expect("spread missing!");
+    assert!((spread - 12.5_f64.sqrt()).abs() < 0.01);
+    assert!(location.last_updated.is_some());
+
+    // estimators only report the raw locations they kept
+    let location =
+        InsertTransmissionLocation::try_from_raw_with(raw, &estimator::Dbscan::default()).unwrap();
+    assert_eq!(location.sample_count, 4);
+    assert_eq!((location.run_count, location.owner_count), (2, 1));
+    let spread = location.spread.expect("spread missing!");
+    assert!((spread - 12.5_f64.sqrt()).abs() < 0.01);
+
+    let location = TransmissionLocation {
+        id: 1,
+        region: location.region,
+        reporting_point: location.reporting_point,
+        lat: location.lat,
+        lon: location.lon,
+        ground_truth: location.ground_truth,
+        uncertainty: location.uncertainty,
+        sample_count: location.sample_count,
+        spread: location.spread,
+        run_count: location.run_count,
+        owner_count: location.owner_count,
+        last_updated: chrono::DateTime::from_timestamp_millis(1_681_000_000_000)
+            .map(|time| time.naive_utc()),
+    };
+    let api = ApiTransmissionLocation::from(&location);
+    assert_eq!(api.properties["sample_count"], 4);
+    assert_eq!(api.properties["owner_count"], 1);
+    assert_eq!(api.properties["ground_truth"], false);
+    assert_eq!(api.properties["last_updated"], "2023-04-09T00:26:40+00:00");
+}
+
+#[test]
+fn test_plan_recompute() {
+    use recompute::{RecomputeReport, plan_recompute};
+
+    let raw_for = |reporting_point: i32, position: (f64, f64)| TransmissionLocationRaw {
+        reporting_point,
+        ..raw_location(offset(position))
+    };
+    let stored_location = |reporting_point: i32, ground_truth: bool| TransmissionLocation {
+        id: i64::from(reporting_point),
+        region: 1,
+        reporting_point,
+        lat: 51.0,
+        lon: 13.0,
+        ground_truth,
+        uncertainty: None,
+        sample_count: 0,
+        spread: None,
+        run_count: 0,
+        owner_count: 0,
+        last_updated: None,
+    };
+
+    let existing = [stored_location(1, true), stored_location(2, false)];
+    let mut raw = vec![
+        raw_for(3, (0.0, 0.0)),
+        raw_for(1, (0.0, 0.0)),
+        raw_for(2, (0.0, 0.0)),
+        raw_for(2, (0.0, 4.0)),
+    ];
+    // two points too far apart to agree on a location
+    raw.extend([raw_for(4, (0.0, 0.0)), raw_for(4, (0.0, 200.0))]);
+
+    let (locations, report) = plan_recompute(&existing, raw, &LocationInferenceConfig::default());
+    assert_eq!(
+        report,
+        RecomputeReport {
+            inserted: vec![3],
+            updated: vec![2],
+            ground_truth: vec![1],
+            failed: vec![(4, TransmissionLocaionError::NoMatches)],
+        }
+    );
+    assert!(!report.is_unchanged());
+
+    assert_eq!(locations.len(), 2);
+    assert!(locations.iter().all(|location| !location.ground_truth));
+    let updated = locations
+        .iter()
+        .find(|location| location.reporting_point == 2)
+        .unwrap();
+    assert_eq!(updated.sample_count, 2);
+    assert!((updated.lat, updated.lon).distance_from(offset((0.0, 2.0))) < 0.01);
+
+    // only ground truth, nothing to do
+    let (locations, report) = plan_recompute(
+        &existing,
+        vec![raw_for(1, (0.0, 0.0))],
+        &LocationInferenceConfig::default(),
+    );
+    assert!(locations.is_empty());
+    assert!(report.is_unchanged());
+}
+
+#[test]
+fn test_locations_geojson() {
+    let mut locations = LocationsJson {
+        region: dresden(),
+        transmission_locations: HashMap::new(),
+    };
+    locations.transmission_locations.insert(
+        0x1a2b,
+        ApiTransmissionLocation {
+            lat: 51.06,
+            lon: 13.75,
+            properties: serde_json::json!({ "ground_truth": true, "sample_count": 3 }),
+        },
+    );
+    locations.transmission_locations.insert(
+        0x0a01,
+        ApiTransmissionLocation {
+            lat: 51.04,
+            lon: 13.72,
+            properties: serde_json::Value::Null,
+        },
+    );
+
+    let geojson = locations.to_geojson();
+    assert_eq!(geojson["type"], "FeatureCollection");
+    assert_eq!(geojson["schema"], SCHEMA);
+    assert_eq!(geojson["region"]["name"], "Dresden");
+    // sorted by reporting point
+    let feature = &geojson["features"][1];
+    assert_eq!(feature["id"], 0x1a2b);
+    assert_eq!(
+        feature["geometry"],
+        serde_json::json!({ "type": "Point", "coordinates": [13.75, 51.06] })
+    );
+    assert_eq!(
+        feature["properties"],
+        serde_json::json!({ "reporting_point": 0x1a2b, "ground_truth": true, "sample_count": 3 })
+    );
+
+    let read = LocationsJson::from_geojson(&geojson).unwrap();
+    assert_eq!(read.region.id, 1);
+    assert_eq!(read.transmission_locations.len(), 2);
+    let location = &read.transmission_locations[&0x1a2b];
+    assert_eq!((location.lat, location.lon), (51.06, 13.75));
+    assert_eq!(
+        location.properties,
+        locations.transmission_locations[&0x1a2b].properties
+    );
+    assert_eq!(
+        read.transmission_locations[&0x0a01].properties,
+        serde_json::json!({})
+    );
+
+    let mut outdated = geojson.clone();
+    outdated["schema"] = serde_json::json!("2");
+    assert!(matches!(
+        LocationsJson::from_geojson(&outdated),
+        Err(LocationsGeoJsonError::SchemaMismatch(schema)) if schema == "2"
+    ));
+    let mut invalid_id = geojson.clone();
+    invalid_id["features"][0]["id"] = serde_json::json!("a01");
+    assert!(matches!(
+        LocationsJson::from_geojson(&invalid_id),
+        Err(LocationsGeoJsonError::InvalidReportingPoint(_))
+    ));
+    let mut invalid_region = geojson;
+    invalid_region["region"] = serde_json::Value::Null;
+    assert!(matches!(
+        LocationsJson::from_geojson(&invalid_region),
+        Err(LocationsGeoJsonError::SerdeJsonError(_))
+    ));
+}
@@ -1,8 +1,18 @@
 //! This module defines structs and enum working with waypoints
 
 use crate::grpc::GrpcWaypoint;
+#[cfg(feature = "trekkie")]
+use crate::locations::gps::GpsPoint;
+use crate::locations::{LocationsJson, TransmissionLocation};
+use crate::telegrams::r09::R09SaveTelegram;
+use crate::timestamp::Timestamp;
+#[cfg(feature = "trekkie")]
+use crate::trekkie::TrekkieRun;
+
 use serde::{Deserialize, Serialize};
 
+use std::fmt;
+
 /// this enum tell the waypoint which source they came from
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum WayPointType {
@@ -31,8 +41,8 @@ pub struct Waypoint {
     pub id: u64,
     /// from which data source this waypoint was constructed
     pub source: WayPointType,
-    /// unix time stamp in milliseconds
-    pub time: u64,
+    /// time stamp, serialized as unix time stamp in milliseconds
+    pub time: Timestamp,
     /// region identifier
     pub region: i64,
     /// latitude
@@ -51,12 +61,38 @@ pub struct Waypoint {
     pub r09_destination_number: Option<i32>,
 }
 
-impl From<GrpcWaypoint> for Waypoint {
-    fn from(waypoint: GrpcWaypoint) -> Self {
-        Waypoint {
+impl TryFrom<GrpcWaypoint> for Waypoint {
+    type Error = WaypointError;
+
+    fn try_from(waypoint: GrpcWaypoint) -> Result<Self, Self::Error> {
+        Ok(Waypoint {
             id: waypoint.id,
             source: WayPointType::from(waypoint.source),
-            time: waypoint.time,
+            time: waypoint
+                .get_time()
+                .map_err(|_| WaypointError::InvalidTime(waypoint.time))?,
+            region: waypoint.region,
+            lat: waypoint.lat,
+            lon: waypoint.lon,
+            line: waypoint.line,
+            run: waypoint.run,
+            delayed: waypoint.delayed,
+            r09_reporting_point: waypoint.r09_reporting_point,
+            r09_destination_number: waypoint.r09_destination_number,
+        })
+    }
+}
+
+impl TryFrom<Waypoint> for GrpcWaypoint {
+    type Error = WaypointError;
+
+    /// Waypoints from before 1970 can't be sent.
+    fn try_from(waypoint: Waypoint) -> Result<Self, Self::Error> {
+        Ok(GrpcWaypoint {
+            id: waypoint.id,
+            source: waypoint.source as i32,
+            time: u64::try_from(waypoint.time)
+                .map_err(|_| WaypointError::TimeBeforeEpoch(waypoint.time))?,
             region: waypoint.region,
             lat: waypoint.lat,
             lon: waypoint.lon,
@@ -65,6 +101,178 @@ impl From<GrpcWaypoint> for Waypoint {
             delayed: waypoint.delayed,
             r09_reporting_point: waypoint.r09_reporting_point,
             r09_destination_number: waypoint.r09_destination_number,
+        })
+    }
+}
+
+/// Error returned when a [`Waypoint`] can't be constructed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WaypointError {
+    /// No transmission location is known for the reporting point of the telegram.
+    UnknownReportingPoint {
+        /// region of the telegram
+        region: i64,
+        /// reporting point of the telegram
+        reporting_point: i32,
+    },
+    /// The transmission location belongs to a different region than the telegram.
+    RegionMismatch {
+        /// region of the telegram
+        telegram: i64,
+        /// region of the transmission location
+        location: i64,
+    },
+    /// The transmission location belongs to a different reporting point than the telegram.
+    ReportingPointMismatch {
+        /// reporting point of the telegram
+        telegram: i32,
+        /// reporting point of the transmission location
+        location: i32,
+    },
+    /// The time stamp can't be represented as [`Timestamp`].
+    InvalidTime(u64),
+    /// The time stamp lies before 1970 and can't be sent as unix time stamp.
+    TimeBeforeEpoch(Timestamp),
+    /// The gps point was recorded during a different trekkie run.
+    RunMismatch {
+        /// trekkie run of the gps point
+        point: uuid::Uuid,
+        /// id of the trekkie run
+        run: uuid::Uuid,
+    },
+    /// The telegram doesn't contain a line.
+    MissingLine,
+    /// The telegram doesn't contain a run number.
+    MissingRun,
+}
+
+impl fmt::Display for WaypointError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WaypointError::UnknownReportingPoint {
+                region,
+                reporting_point,
+            } => write!(
+                f,
+                "no location known for reporting point {reporting_point} in region {region}"
+            ),
+            WaypointError::RegionMismatch { telegram, location } => write!(
+                f,
+                "telegram from region {telegram} doesn't match location in region {location}"
+            ),
+            WaypointError::ReportingPointMismatch { telegram, location } => write!(
+                f,
+                "telegram with reporting point {telegram} doesn't match location of reporting point {location}"
+            ),
+            WaypointError::InvalidTime(value) => write!(f, "invalid timestamp: {value}"),
+            WaypointError::TimeBeforeEpoch(time) => {
+                write!(f, "timestamp {time} lies before 1970")
+            }
+            WaypointError::RunMismatch { point, run } => write!(
+                f,
+                "gps point of trekkie run {point} doesn't belong to trekkie run {run}"
+            ),
+            WaypointError::MissingLine => write!(f, "telegram doesn't contain a line"),
+            WaypointError::MissingRun => write!(f, "telegram doesn't contain a run number"),
+        }
+    }
+}
+
+impl std::error::Error for WaypointError {}
+
+impl Waypoint {
+    /// Creates a waypoint at the transmission location of the telegram. The waypoint gets the id
+    /// of the telegram, or 0 if the telegram wasn't saved yet.
+    pub fn from_r09(
+        telegram: &R09SaveTelegram,
+        location: &TransmissionLocation,
+    ) -> Result<Waypoint, WaypointError> {
+        if telegram.region != location.region {
+            return Err(WaypointError::RegionMismatch {
+                telegram: telegram.region,
+                location: location.region,
+            });
+        }
+        if telegram.reporting_point != location.reporting_point {
+            return Err(WaypointError::ReportingPointMismatch {
+                telegram: telegram.reporting_point,
+                location: location.reporting_point,
+            });
         }
+
+        Self::from_r09_at(telegram, location.lat, location.lon)
+    }
+
+    /// Same as [`Waypoint::from_r09`] but looks up the transmission location of the telegram in
+    /// the locations of its region.
+    pub fn from_r09_locations(
+        telegram: &R09SaveTelegram,
+        locations: &LocationsJson,
+    ) -> Result<Waypoint, WaypointError> {
+        if telegram.region != locations.region.id {
+            return Err(WaypointError::RegionMismatch {
+                telegram: telegram.region,
+                location: locations.region.id,
+            });
+        }
+
+        let location = locations
+            .transmission_locations
+            .get(&i64::from(telegram.reporting_point))
+            .ok_or(WaypointError::UnknownReportingPoint {
+                region: telegram.region,
+                reporting_point: telegram.reporting_point,
+            })?;
+
+        Self::from_r09_at(telegram, location.lat, location.lon)
+    }
+
+    /// Creates a waypoint from a gps point, line, run and region are taken from the trekkie run
+    /// the point was recorded during.
+    #[cfg(feature = "trekkie")]
+    pub fn from_gps(point: &GpsPoint, run: &TrekkieRun) -> Result<Waypoint, WaypointError> {
+        if point.trekkie_run != run.id {
+            return Err(WaypointError::RunMismatch {
+                point: point.trekkie_run,
+                run: run.id,
+            });
+        }
+
+        Ok(Waypoint {
+            id: u64::try_from(point.id).unwrap_or_default(),
+            source: WayPointType::TrekkieGPS,
+            time: Timestamp::from(point.timestamp),
+            region: run.region,
+            lat: point.lat,
+            lon: point.lon,
+            line: run.line,
+            run: run.run,
+            delayed: None,
+            r09_reporting_point: None,
+            r09_destination_number: None,
+        })
+    }
+
+    fn from_r09_at(
+        telegram: &R09SaveTelegram,
+        lat: f64,
+        lon: f64,
+    ) -> Result<Waypoint, WaypointError> {
+        Ok(Waypoint {
+            id: telegram
+                .id
+                .and_then(|id| u64::try_from(id).ok())
+                .unwrap_or_default(),
+            source: WayPointType::R09Telegram,
+            time: Timestamp::from(telegram.time),
+            region: telegram.region,
+            lat,
+            lon,
+            line: telegram.line.ok_or(WaypointError::MissingLine)?,
+            run: telegram.run_number.ok_or(WaypointError::MissingRun)?,
+            delayed: telegram.delay.map(|delay| delay as f32),
+            r09_reporting_point: Some(telegram.reporting_point),
+            r09_destination_number: telegram.destination_number,
+        })
     }
 }
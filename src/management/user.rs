@@ -79,7 +79,9 @@ impl Hash for Role {
 }
 
 impl FromSql<diesel::sql_types::Integer, Pg> for Role {
-    fn from_sql(bytes: diesel::backend::RawValue<'_, Pg>) -> deserialize::Result<Self> {
+    fn from_sql(
+        bytes: <Pg as diesel::backend::Backend>::RawValue<'_>,
+    ) -> deserialize::Result<Self> {
         let v: i32 = i32::from_sql(bytes)?;
         let res: Self = v.try_into()?;
         Ok(res)
@@ -209,7 +211,7 @@ impl AuthorizedUser {
                 for association in associations {
                     roles
                         .entry(association.organization)
-                        .or_insert_with(Vec::new)
+                        .or_default()
                         .push(association.role);
                 }
 
@@ -268,7 +270,7 @@ impl Serialize for User {
 pub fn hash_password(password: &String) -> Option<String> {
     let default_salt_path = String::from("/run/secrets/clicky_bunty_salt");
     let salt_path = std::env::var("SALT_PATH").unwrap_or(default_salt_path);
-    let salt = SaltString::b64_encode(std::fs::read(salt_path).unwrap().as_slice()).unwrap();
+    let salt = SaltString::encode_b64(std::fs::read(salt_path).unwrap().as_slice()).unwrap();
 
     match Pbkdf2.hash_password(password.as_bytes(), &salt) {
         Ok(password_hash) => PasswordHash::new(&password_hash.to_string())
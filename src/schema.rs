@@ -65,6 +65,12 @@ diesel::table! {
         lat -> Float8,
         lon -> Float8,
         ground_truth -> Bool,
+        uncertainty -> Nullable<Float8>,
+        sample_count -> Int4,
+        spread -> Nullable<Float8>,
+        run_count -> Int4,
+        owner_count -> Int4,
+        last_updated -> Nullable<Timestamp>,
     }
 }
 
@@ -77,6 +83,8 @@ diesel::table! {
         lon -> Float8,
         trekkie_run -> Uuid,
         run_owner -> Uuid,
+        accuracy -> Nullable<Float8>,
+        time_offset -> Nullable<Int8>,
     }
 }
 
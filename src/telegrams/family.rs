@@ -0,0 +1,348 @@
+//!
+//! Structural decoders for the VDV 420 R and C telegram families. The header is validated and the
+//! payload is kept as bytes so it can be analyzed further. The fields these telegrams share with
+//! R09 are read from the same positions by the `get_*` accessors, the rest of the payload is not
+//! interpreted. R09 telegrams are fully decoded by [`R09Telegram`][super::r09::R09Telegram].
+//!
+//! Every telegram starts with the same header byte:
+//!
+//! | bits | field                                              |
+//! |------|----------------------------------------------------|
+//! | 4    | mode, the number of the telegram inside its family |
+//! | 4    | length of the payload after the header in bytes    |
+//!
+//! The common fields inside the payload:
+//!
+//! | bits | field                                   |
+//! |------|-----------------------------------------|
+//! | 8    | delay and train length, not decoded     |
+//! | 16   | reporting point, see [`ReportingPoint`] |
+//! | 4    | priority and direction request          |
+//! | 12   | line, 3 BCD digits                      |
+//!
+
+use super::r09::ReportingPoint;
+use super::raw::RawTelegram;
+use super::{GetTelegramType, TelegramType};
+
+use serde::{Deserialize, Serialize};
+
+use std::fmt;
+
+/// Longest payload the length nibble of the header can announce.
+pub const MAX_PAYLOAD_LENGTH: usize = 0x0f;
+
+/// Error returned when a [`RawTelegram`] can not be decoded into a [`RTelegram`] or
+/// [`CTelegram`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FamilyDecodeError {
+    /// The telegram type belongs to the other family.
+    WrongFamily(TelegramType),
+    /// The frame is shorter than announced by the header.
+    Truncated {
+        /// number of bytes announced by the header
+        expected: usize,
+        /// number of bytes that were received
+        actual: usize,
+    },
+    /// The frame is longer than announced by the header.
+    TrailingBytes {
+        /// number of bytes announced by the header
+        expected: usize,
+        /// number of bytes that were received
+        actual: usize,
+    },
+    /// The mode nibble of the header doesn't match the telegram type.
+    ModeMismatch {
+        /// mode of the telegram type
+        expected: u8,
+        /// mode inside the header
+        found: u8,
+    },
+}
+
+impl fmt::Display for FamilyDecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FamilyDecodeError::WrongFamily(telegram_type) => {
+                write!(f, "{telegram_type:?} belongs to the other telegram family")
+            }
+            FamilyDecodeError::Truncated { expected, actual } => {
+                write!(
+                    f,
+                    "telegram truncated: expected {expected} bytes, got {actual}"
+                )
+            }
+            FamilyDecodeError::TrailingBytes { expected, actual } => {
+                write!(f, "trailing bytes: expected {expected} bytes, got {actual}")
+            }
+            FamilyDecodeError::ModeMismatch { expected, found } => {
+                write!(f, "expected mode {expected}, header contains {found}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for FamilyDecodeError {}
+
+/// Error returned by [`RTelegram::try_encode`] and [`CTelegram::try_encode`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FamilyEncodeError {
+    /// The payload is longer than [`MAX_PAYLOAD_LENGTH`] bytes and can't be announced by the
+    /// header.
+    PayloadTooLong(usize),
+}
+
+impl fmt::Display for FamilyEncodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FamilyEncodeError::PayloadTooLong(length) => write!(
+                f,
+                "payload of {length} bytes exceeds {MAX_PAYLOAD_LENGTH} bytes"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for FamilyEncodeError {}
+
+/// Structurally decoded telegram of the R family (**R00** - **R15**). Only the header and the
+/// fields shared with R09 are decoded, see the [module documentation][self].
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RTelegram {
+    /// Telegram type, see [`TelegramType`]
+    pub telegram_type: TelegramType,
+    /// Bytes following the header
+    pub payload: Vec<u8>,
+}
+
+/// Structurally decoded telegram of the C family (**C00** - **C15**). Only the header and the
+/// fields shared with R09 are decoded, see the [module documentation][self].
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CTelegram {
+    /// Telegram type, see [`TelegramType`]
+    pub telegram_type: TelegramType,
+    /// Bytes following the header
+    pub payload: Vec<u8>,
+}
+
+/// Validates the header of the frame and returns the payload.
+fn decode_frame(raw: &RawTelegram) -> Result<Vec<u8>, FamilyDecodeError> {
+    let header = *raw.data.first().ok_or(FamilyDecodeError::Truncated {
+        expected: 1,
+        actual: 0,
+    })?;
+
+    let expected_mode = raw.telegram_type.mode();
+    let mode = header >> 4;
+    if mode != expected_mode {
+        return Err(FamilyDecodeError::ModeMismatch {
+            expected: expected_mode,
+            found: mode,
+        });
+    }
+
+    let expected = 1 + (header & 0x0f) as usize;
+    if raw.data.len() < expected {
+        return Err(FamilyDecodeError::Truncated {
+            expected,
+            actual: raw.data.len(),
+        });
+    }
+    if raw.data.len() > expected {
+        return Err(FamilyDecodeError::TrailingBytes {
+            expected,
+            actual: raw.data.len(),
+        });
+    }
+
+    Ok(raw.data[1..].to_vec())
+}
+
+/// Prepends the header to the payload, the length nibble is truncated for payloads longer than
+/// [`MAX_PAYLOAD_LENGTH`].
+fn encode_frame(telegram_type: &TelegramType, payload: &[u8]) -> Vec<u8> {
+    let mut data = Vec::with_capacity(payload.len() + 1);
+    data.push((telegram_type.mode() << 4) | (payload.len() as u8 & 0x0f));
+    data.extend_from_slice(payload);
+    data
+}
+
+/// Same as [`encode_frame`] but rejects payloads the header can't announce.
+fn try_encode_frame(
+    telegram_type: &TelegramType,
+    payload: &[u8],
+) -> Result<Vec<u8>, FamilyEncodeError> {
+    if payload.len() > MAX_PAYLOAD_LENGTH {
+        return Err(FamilyEncodeError::PayloadTooLong(payload.len()));
+    }
+
+    Ok(encode_frame(telegram_type, payload))
+}
+
+/// Splits bytes into nibbles, most significant nibble first.
+fn nibbles(payload: &[u8]) -> Vec<u8> {
+    payload
+        .iter()
+        .flat_map(|byte| [byte >> 4, byte & 0x0f])
+        .collect()
+}
+
+/// Reads the reporting point from bytes 1 and 2 of the payload.
+fn reporting_point(payload: &[u8]) -> Option<ReportingPoint> {
+    match payload {
+        [_, high, low, ..] => Some(ReportingPoint::from(u16::from_be_bytes([*high, *low]))),
+        _ => None,
+    }
+}
+
+/// Reads the BCD encoded line from nibbles 7 to 9 of the payload.
+fn line(payload: &[u8]) -> Option<u32> {
+    nibbles(payload.get(..5)?)[7..10]
+        .iter()
+        .try_fold(0, |line, &digit| {
+            (digit <= 9).then_some(line * 10 + u32::from(digit))
+        })
+}
+
+impl RTelegram {
+    /// Mode of the telegram, the number of the telegram inside its family.
+    pub fn get_mode(&self) -> u8 {
+        self.telegram_type.mode()
+    }
+
+    /// Length of the payload in bytes.
+    pub fn get_length(&self) -> usize {
+        self.payload.len()
+    }
+
+    /// Reporting point of the telegram, `None` if the payload is too short.
+    pub fn get_reporting_point(&self) -> Option<ReportingPoint> {
+        reporting_point(&self.payload)
+    }
+
+    /// Line of the telegram, `None` if the payload is too short or the line isn't valid BCD.
+    pub fn get_line(&self) -> Option<u32> {
+        line(&self.payload)
+    }
+
+    /// Returns the payload split into nibbles, most significant nibble first.
+    pub fn nibbles(&self) -> Vec<u8> {
+        nibbles(&self.payload)
+    }
+
+    /// Encodes the telegram including its header.
+    ///
+    /// The length nibble is silently truncated for payloads longer than [`MAX_PAYLOAD_LENGTH`],
+    /// use [`RTelegram::try_encode`] if the telegram isn't known to be valid.
+    pub fn encode(&self) -> Vec<u8> {
+        encode_frame(&self.telegram_type, &self.payload)
+    }
+
+    /// Encodes the telegram including its header, see [`RTelegram::encode`]. Returns
+    /// [`FamilyEncodeError::PayloadTooLong`] instead of truncating the length nibble.
+    pub fn try_encode(&self) -> Result<Vec<u8>, FamilyEncodeError> {
+        try_encode_frame(&self.telegram_type, &self.payload)
+    }
+}
+
+impl CTelegram {
+    /// Mode of the telegram, the number of the telegram inside its family.
+    pub fn get_mode(&self) -> u8 {
+        self.telegram_type.mode()
+    }
+
+    /// Length of the payload in bytes.
+    pub fn get_length(&self) -> usize {
+        self.payload.len()
+    }
+
+    /// Reporting point of the telegram, `None` if the payload is too short.
+    pub fn get_reporting_point(&self) -> Option<ReportingPoint> {
+        reporting_point(&self.payload)
+    }
+
+    /// Line of the telegram, `None` if the payload is too short or the line isn't valid BCD.
+    pub fn get_line(&self) -> Option<u32> {
+        line(&self.payload)
+    }
+
+    /// Returns the payload split into nibbles, most significant nibble first.
+    pub fn nibbles(&self) -> Vec<u8> {
+        nibbles(&self.payload)
+    }
+
+    /// Encodes the telegram including its header.
+    ///
+    /// The length nibble is silently truncated for payloads longer than [`MAX_PAYLOAD_LENGTH`],
+    /// use [`CTelegram::try_encode`] if the telegram isn't known to be valid.
+    pub fn encode(&self) -> Vec<u8> {
+        encode_frame(&self.telegram_type, &self.payload)
+    }
+
+    /// Encodes the telegram including its header, see [`CTelegram::encode`]. Returns
+    /// [`FamilyEncodeError::PayloadTooLong`] instead of truncating the length nibble.
+    pub fn try_encode(&self) -> Result<Vec<u8>, FamilyEncodeError> {
+        try_encode_frame(&self.telegram_type, &self.payload)
+    }
+}
+
+impl TryFrom<&RawTelegram> for RTelegram {
+    type Error = FamilyDecodeError;
+
+    fn try_from(raw: &RawTelegram) -> Result<Self, Self::Error> {
+        if !raw.telegram_type.is_r() {
+            return Err(FamilyDecodeError::WrongFamily(raw.telegram_type.clone()));
+        }
+
+        Ok(RTelegram {
+            telegram_type: raw.telegram_type.clone(),
+            payload: decode_frame(raw)?,
+        })
+    }
+}
+
+impl TryFrom<&RawTelegram> for CTelegram {
+    type Error = FamilyDecodeError;
+
+    fn try_from(raw: &RawTelegram) -> Result<Self, Self::Error> {
+        if !raw.telegram_type.is_c() {
+            return Err(FamilyDecodeError::WrongFamily(raw.telegram_type.clone()));
+        }
+
+        Ok(CTelegram {
+            telegram_type: raw.telegram_type.clone(),
+            payload: decode_frame(raw)?,
+        })
+    }
+}
+
+impl From<RTelegram> for RawTelegram {
+    fn from(telegram: RTelegram) -> Self {
+        RawTelegram {
+            data: telegram.encode(),
+            telegram_type: telegram.telegram_type,
+        }
+    }
+}
+
+impl From<CTelegram> for RawTelegram {
+    fn from(telegram: CTelegram) -> Self {
+        RawTelegram {
+            data: telegram.encode(),
+            telegram_type: telegram.telegram_type,
+        }
+    }
+}
+
+impl GetTelegramType for RTelegram {
+    fn get_type(&self) -> TelegramType {
+        self.telegram_type.clone()
+    }
+}
+
+impl GetTelegramType for CTelegram {
+    fn get_type(&self) -> TelegramType {
+        self.telegram_type.clone()
+    }
+}
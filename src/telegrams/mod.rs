@@ -1,5 +1,12 @@
+pub mod family;
 pub mod r09;
 pub mod raw;
+#[cfg(test)]
+mod tests;
+
+use family::{CTelegram, RTelegram};
+use r09::{R09ConversionError, R09SaveTelegram, R09Telegram};
+use raw::{RawSaveTelegram, RawTelegram};
 
 use std::hash::{Hash, Hasher};
 
@@ -37,7 +44,7 @@ pub struct AuthenticationMeta {
 
 #[allow(missing_docs)]
 /// All the different Telegram flavors
-#[derive(Serialize, Deserialize, FromPrimitive, Debug, Clone)]
+#[derive(Serialize, Deserialize, FromPrimitive, Debug, Clone, PartialEq, Eq)]
 pub enum TelegramType {
     R00 = 0,
     R01 = 1,
@@ -48,7 +55,8 @@ pub enum TelegramType {
     R06 = 6,
     R07 = 7,
     R08 = 8,
-    /// The actually interesting and currently only telegram which gets properly decoded.
+    /// The actually interesting and currently only telegram which gets properly decoded. All
+    /// other telegrams are only decoded structurally, see [`family`].
     R09 = 9,
     R10 = 10,
     R11 = 11,
@@ -80,6 +88,23 @@ impl Hash for TelegramType {
     }
 }
 
+impl TelegramType {
+    /// Returns true if the telegram belongs to the R family (**R00** - **R15**).
+    pub fn is_r(&self) -> bool {
+        (self.clone() as u8) < 16
+    }
+
+    /// Returns true if the telegram belongs to the C family (**C00** - **C15**).
+    pub fn is_c(&self) -> bool {
+        !self.is_r()
+    }
+
+    /// Returns the mode, the number of the telegram inside its family.
+    pub fn mode(&self) -> u8 {
+        self.clone() as u8 % 16
+    }
+}
+
 /// Returns [`TelegramType`] of an implemented telegram type.
 pub trait GetTelegramType {
     /// Returns [`TelegramType`] of an implemented telegram type.
@@ -87,3 +112,99 @@ pub trait GetTelegramType {
     where
         Self: Sized;
 }
+
+/// Any telegram that is sent to **data-accumulator**. Serialized with a `kind` tag so receivers
+/// can tell the different telegram flavors apart.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Telegram {
+    /// Properly decoded R09 telegram, see [`R09Telegram`].
+    R09(R09Telegram),
+    /// Structurally decoded telegram of the R family, see [`RTelegram`].
+    R(RTelegram),
+    /// Structurally decoded telegram of the C family, see [`CTelegram`].
+    C(CTelegram),
+    /// Intercepted bytes which couldn't be decoded, see [`RawTelegram`].
+    Raw(RawTelegram),
+}
+
+/// Database representation of a [`Telegram`], R09 telegrams go into their own table while every
+/// other flavor is stored as raw bytes.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub enum SaveTelegram {
+    /// see [`R09SaveTelegram`]
+    R09(R09SaveTelegram),
+    /// see [`RawSaveTelegram`]
+    Raw(RawSaveTelegram),
+}
+
+/// Telegram of any flavor with embedded auth information that is sent to **data-accumulator**.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct ReceiveTelegram {
+    /// struct that holds all relevant authentication information of a telegram. Most importantly
+    /// Station UUID and Station Token which are checked by data-accumulator.
+    #[serde(flatten)]
+    pub auth: AuthenticationMeta,
+    /// Telegram Data
+    #[serde(flatten)]
+    pub data: Telegram,
+}
+
+impl Telegram {
+    /// Decodes a [`RawTelegram`] as far as possible. Telegrams that fail to decode are returned
+    /// as [`Telegram::Raw`].
+    pub fn decode(raw: RawTelegram) -> Telegram {
+        if raw.telegram_type == TelegramType::R09 {
+            if let Ok(telegram) = R09Telegram::try_from(&raw) {
+                return Telegram::R09(telegram);
+            }
+        } else if raw.telegram_type.is_r() {
+            if let Ok(telegram) = RTelegram::try_from(&raw) {
+                return Telegram::R(telegram);
+            }
+        } else if let Ok(telegram) = CTelegram::try_from(&raw) {
+            return Telegram::C(telegram);
+        }
+
+        Telegram::Raw(raw)
+    }
+
+    /// Takes the telegram and meta data to create the matching [`SaveTelegram`] which then can be
+    /// written to the database. Fails if a value of a R09 telegram doesn't fit into its database
+    /// column. R and C telegrams are only decoded structurally, so they are stored as raw
+    /// telegrams with their original bytes.
+    pub fn into_save(
+        self,
+        meta: TelegramMetaInformation,
+    ) -> Result<SaveTelegram, R09ConversionError> {
+        Ok(match self {
+            Telegram::R09(telegram) => {
+                SaveTelegram::R09(R09SaveTelegram::try_from((telegram, meta))?)
+            }
+            Telegram::R(telegram) => {
+                SaveTelegram::Raw(RawSaveTelegram::from(telegram.into(), meta))
+            }
+            Telegram::C(telegram) => {
+                SaveTelegram::Raw(RawSaveTelegram::from(telegram.into(), meta))
+            }
+            Telegram::Raw(telegram) => SaveTelegram::Raw(RawSaveTelegram::from(telegram, meta)),
+        })
+    }
+}
+
+impl GetTelegramType for Telegram {
+    fn get_type(&self) -> TelegramType {
+        match self {
+            Telegram::R09(telegram) => telegram.get_type(),
+            Telegram::R(telegram) => telegram.get_type(),
+            Telegram::C(telegram) => telegram.get_type(),
+            Telegram::Raw(telegram) => telegram.get_type(),
+        }
+    }
+}
+
+impl Hash for ReceiveTelegram {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.data.hash(state);
+    }
+}
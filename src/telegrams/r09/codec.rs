@@ -0,0 +1,406 @@
+//!
+//! Bit level decoder and encoder for VDV 420 R09 telegrams.
+//!
+//! All R09 flavors share the same header and base layout, **R09.16** appends the vehicle number
+//! and **R09.18** additionally the operator. Fields are read MSB first, numbers that are
+//! displayed to passengers (line, run, destination, vehicle, operator) are BCD encoded.
+//!
+//! | bits | field                                              | types      |
+//! |------|----------------------------------------------------|------------|
+//! | 4    | mode, always `9`                                   | all        |
+//! | 4    | length of the telegram after the header in bytes   | all        |
+//! | 1    | delay present (ZV)                                 | all        |
+//! | 1    | delay sign, `1` means ahead of schedule            | all        |
+//! | 3    | delay in minutes                                   | all        |
+//! | 3    | train length                                       | all        |
+//! | 16   | reporting point, see [`ReportingPoint`]            | all        |
+//! | 2    | priority                                           | all        |
+//! | 2    | direction request                                  | all        |
+//! | 12   | line, 3 BCD digits                                 | all        |
+//! | 8    | run number, 2 BCD digits                           | all        |
+//! | 12   | destination number, 3 BCD digits                   | all        |
+//! | 4    | reserved                                           | all        |
+//! | 16   | vehicle number, 4 BCD digits                       | R16, R18   |
+//! | 8    | operator, 2 BCD digits                             | R18        |
+//!
+
+use super::{R09Telegram, R09Type, ReportingPoint};
+use crate::telegrams::TelegramType;
+use crate::telegrams::raw::RawTelegram;
+
+use std::fmt;
+
+/// Mode nibble every R09 telegram starts with.
+pub const R09_MODE: u8 = 9;
+
+/// Error returned when a byte slice can not be decoded into a [`R09Telegram`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum R09DecodeError {
+    /// The frame is shorter than the telegram type requires.
+    Truncated {
+        /// number of bytes the telegram type requires
+        expected: usize,
+        /// number of bytes that were received
+        actual: usize,
+    },
+    /// The frame is longer than the telegram type allows.
+    TrailingBytes {
+        /// number of bytes the telegram type requires
+        expected: usize,
+        /// number of bytes that were received
+        actual: usize,
+    },
+    /// The mode nibble of the header is not [`R09_MODE`].
+    InvalidMode(u8),
+    /// The length nibble of the header doesn't correspond to any [`R09Type`].
+    UnknownLength(u8),
+    /// The header announces a different [`R09Type`] than the one requested.
+    TypeMismatch {
+        /// type which was requested by the caller
+        expected: R09Type,
+        /// type which is announced by the telegram header
+        found: R09Type,
+    },
+    /// A BCD encoded field contains a nibble greater than 9.
+    InvalidBcd {
+        /// name of the field which failed to decode
+        field: &'static str,
+        /// offending nibble
+        nibble: u8,
+    },
+    /// The [`RawTelegram`] is not a R09 telegram.
+    NotR09(TelegramType),
+}
+
+impl fmt::Display for R09DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            R09DecodeError::Truncated { expected, actual } => {
+                write!(
+                    f,
+                    "telegram truncated: expected {expected} bytes, got {actual}"
+                )
+            }
+            R09DecodeError::TrailingBytes { expected, actual } => {
+                write!(f, "trailing bytes: expected {expected} bytes, got {actual}")
+            }
+            R09DecodeError::InvalidMode(mode) => write!(f, "invalid mode {mode} for R09 telegram"),
+            R09DecodeError::UnknownLength(length) => {
+                write!(f, "no R09 type with payload length {length}")
+            }
+            R09DecodeError::TypeMismatch { expected, found } => {
+                write!(f, "expected {expected} telegram, header announces {found}")
+            }
+            R09DecodeError::InvalidBcd { field, nibble } => {
+                write!(f, "invalid BCD digit {nibble} in field {field}")
+            }
+            R09DecodeError::NotR09(telegram_type) => {
+                write!(f, "{telegram_type:?} is not a R09 telegram")
+            }
+        }
+    }
+}
+
+impl std::error::Error for R09DecodeError {}
+
+/// Error returned by [`R09Telegram::try_encode`] if a value doesn't fit into its VDV 420 field.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum R09EncodeError {
+    /// The value is too large (or negative) for the width of the field.
+    OutOfRange {
+        /// name of the field which failed to encode
+        field: &'static str,
+        /// offending value
+        value: i64,
+    },
+}
+
+impl fmt::Display for R09EncodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            R09EncodeError::OutOfRange { field, value } => {
+                write!(f, "value {value} doesn't fit into field {field}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for R09EncodeError {}
+
+impl R09Type {
+    /// Number of bytes following the header for this telegram type.
+    pub fn payload_length(&self) -> usize {
+        match self {
+            R09Type::R14 => 8,
+            R09Type::R16 => 10,
+            R09Type::R18 => 11,
+        }
+    }
+
+    /// Returns the [`R09Type`] which is announced by the length nibble of the header.
+    pub fn from_payload_length(length: u8) -> Option<R09Type> {
+        match length {
+            8 => Some(R09Type::R14),
+            10 => Some(R09Type::R16),
+            11 => Some(R09Type::R18),
+            _ => None,
+        }
+    }
+}
+
+/// Reads big endian bit fields from a byte slice. The caller is responsible for checking the
+/// length of the slice beforehand.
+struct BitReader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(data: &'a [u8]) -> BitReader<'a> {
+        BitReader { data, position: 0 }
+    }
+
+    fn read(&mut self, bits: usize) -> u32 {
+        let mut value = 0u32;
+        for _ in 0..bits {
+            let byte = self.data[self.position / 8];
+            let bit = (byte >> (7 - self.position % 8)) & 1;
+            value = (value << 1) | bit as u32;
+            self.position += 1;
+        }
+        value
+    }
+
+    fn read_bcd(&mut self, digits: usize, field: &'static str) -> Result<u32, R09DecodeError> {
+        let mut value = 0u32;
+        for _ in 0..digits {
+            let nibble = self.read(4) as u8;
+            if nibble > 9 {
+                return Err(R09DecodeError::InvalidBcd { field, nibble });
+            }
+            value = value * 10 + nibble as u32;
+        }
+        Ok(value)
+    }
+}
+
+/// Writes big endian bit fields into a byte vector. Values are truncated to the field width.
+struct BitWriter {
+    data: Vec<u8>,
+    position: usize,
+}
+
+impl BitWriter {
+    fn new() -> BitWriter {
+        BitWriter {
+            data: Vec::new(),
+            position: 0,
+        }
+    }
+
+    fn write(&mut self, value: u32, bits: usize) {
+        for i in (0..bits).rev() {
+            if self.position.is_multiple_of(8) {
+                self.data.push(0);
+            }
+            let bit = ((value >> i) & 1) as u8;
+            let last = self.data.len() - 1;
+            self.data[last] |= bit << (7 - self.position % 8);
+            self.position += 1;
+        }
+    }
+
+    fn write_bcd(&mut self, value: u32, digits: usize) {
+        for i in (0..digits).rev() {
+            self.write((value / 10u32.pow(i as u32)) % 10, 4);
+        }
+    }
+}
+
+/// Returns [`R09EncodeError::OutOfRange`] if `value` is not within `0..=max`.
+fn check_range(field: &'static str, value: i64, max: i64) -> Result<(), R09EncodeError> {
+    if (0..=max).contains(&value) {
+        Ok(())
+    } else {
+        Err(R09EncodeError::OutOfRange { field, value })
+    }
+}
+
+/// Reads the header and returns the [`R09Type`] it announces.
+fn decode_header(data: &[u8]) -> Result<R09Type, R09DecodeError> {
+    let header = *data.first().ok_or(R09DecodeError::Truncated {
+        expected: 1,
+        actual: 0,
+    })?;
+
+    let mode = header >> 4;
+    if mode != R09_MODE {
+        return Err(R09DecodeError::InvalidMode(mode));
+    }
+
+    let length = header & 0x0f;
+    R09Type::from_payload_length(length).ok_or(R09DecodeError::UnknownLength(length))
+}
+
+/// Decodes the VDV 420 bitstream of a R09 telegram. The header has to announce the given
+/// `r09_type`, see the [module documentation][self] for the expected layout.
+pub fn decode(data: &[u8], r09_type: R09Type) -> Result<R09Telegram, R09DecodeError> {
+    let found = decode_header(data)?;
+    if found != r09_type {
+        return Err(R09DecodeError::TypeMismatch {
+            expected: r09_type,
+            found,
+        });
+    }
+
+    let expected = 1 + r09_type.payload_length();
+    if data.len() < expected {
+        return Err(R09DecodeError::Truncated {
+            expected,
+            actual: data.len(),
+        });
+    }
+    if data.len() > expected {
+        return Err(R09DecodeError::TrailingBytes {
+            expected,
+            actual: data.len(),
+        });
+    }
+
+    let mut reader = BitReader::new(&data[1..]);
+
+    let delay_present = reader.read(1) == 1;
+    let ahead = reader.read(1) == 1;
+    let delay_minutes = reader.read(3) as i32;
+    let train_length = reader.read(3) as i32;
+
+    let reporting_point = ReportingPoint::from(reader.read(16) as u16);
+
+    let priority = reader.read(2) as u8;
+    let direction_request = reader.read(2) as u8;
+    let line = reader.read_bcd(3, "line")?;
+    let run_number = reader.read_bcd(2, "run_number")?;
+    let destination_number = reader.read_bcd(3, "destination_number")?;
+    // reserved
+    reader.read(4);
+
+    let vehicle_number = match r09_type {
+        R09Type::R16 | R09Type::R18 => Some(reader.read_bcd(4, "vehicle_number")?),
+        R09Type::R14 => None,
+    };
+
+    let operator = match r09_type {
+        R09Type::R18 => Some(reader.read_bcd(2, "operator")? as u8),
+        R09Type::R14 | R09Type::R16 => None,
+    };
+
+    let delay = if delay_present {
+        Some(if ahead { -delay_minutes } else { delay_minutes })
+    } else {
+        None
+    };
+
+    Ok(R09Telegram {
+        r09_type,
+        delay,
+        reporting_point: reporting_point.value(),
+        junction: reporting_point.junction(),
+        direction: reporting_point.direction(),
+        request_status: reporting_point.request_status().into(),
+        priority: Some(priority),
+        direction_request: Some(direction_request),
+        line: Some(line),
+        run_number: Some(run_number),
+        destination_number: Some(destination_number),
+        train_length: Some(train_length),
+        vehicle_number,
+        operator,
+    })
+}
+
+impl TryFrom<&RawTelegram> for R09Telegram {
+    type Error = R09DecodeError;
+
+    fn try_from(raw: &RawTelegram) -> Result<Self, Self::Error> {
+        if raw.telegram_type != TelegramType::R09 {
+            return Err(R09DecodeError::NotR09(raw.telegram_type.clone()));
+        }
+
+        let r09_type = decode_header(&raw.data)?;
+        decode(&raw.data, r09_type)
+    }
+}
+
+impl R09Telegram {
+    /// Encodes the telegram into the VDV 420 bitstream, see the [module documentation][self] for
+    /// the layout. Only the composite `reporting_point` is transmitted, `junction`, `direction` and
+    /// `request_status` are ignored. Missing optional fields are encoded as `0`.
+    ///
+    /// Values which don't fit into their field are silently truncated, use
+    /// [`R09Telegram::try_encode`] if the telegram isn't known to be valid.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut writer = BitWriter::new();
+
+        writer.write(R09_MODE as u32, 4);
+        writer.write(self.r09_type.payload_length() as u32, 4);
+
+        let delay = self.delay.unwrap_or(0);
+        writer.write(self.delay.is_some() as u32, 1);
+        writer.write((delay < 0) as u32, 1);
+        writer.write(delay.unsigned_abs(), 3);
+        writer.write(self.train_length.unwrap_or(0) as u32, 3);
+
+        writer.write(self.reporting_point, 16);
+
+        writer.write(self.priority.unwrap_or(0) as u32, 2);
+        writer.write(self.direction_request.unwrap_or(0) as u32, 2);
+        writer.write_bcd(self.line.unwrap_or(0), 3);
+        writer.write_bcd(self.run_number.unwrap_or(0), 2);
+        writer.write_bcd(self.destination_number.unwrap_or(0), 3);
+        // reserved
+        writer.write(0, 4);
+
+        if let R09Type::R16 | R09Type::R18 = self.r09_type {
+            writer.write_bcd(self.vehicle_number.unwrap_or(0), 4);
+        }
+        if let R09Type::R18 = self.r09_type {
+            writer.write_bcd(self.operator.unwrap_or(0) as u32, 2);
+        }
+
+        writer.data
+    }
+
+    /// Same as [`R09Telegram::encode`] but fails if any value doesn't fit into its field.
+    pub fn try_encode(&self) -> Result<Vec<u8>, R09EncodeError> {
+        if let Some(delay) = self.delay {
+            check_range("delay", i64::from(delay).abs(), 7)?;
+        }
+        if let Some(train_length) = self.train_length {
+            check_range("train_length", train_length.into(), 7)?;
+        }
+        check_range("reporting_point", self.reporting_point.into(), 0xffff)?;
+        if let Some(priority) = self.priority {
+            check_range("priority", priority.into(), 3)?;
+        }
+        if let Some(direction_request) = self.direction_request {
+            check_range("direction_request", direction_request.into(), 3)?;
+        }
+        if let Some(line) = self.line {
+            check_range("line", line.into(), 999)?;
+        }
+        if let Some(run_number) = self.run_number {
+            check_range("run_number", run_number.into(), 99)?;
+        }
+        if let Some(destination_number) = self.destination_number {
+            check_range("destination_number", destination_number.into(), 999)?;
+        }
+        if let Some(vehicle_number) = self.vehicle_number {
+            check_range("vehicle_number", vehicle_number.into(), 9999)?;
+        }
+        if let Some(operator) = self.operator {
+            check_range("operator", operator.into(), 99)?;
+        }
+
+        Ok(self.encode())
+    }
+}
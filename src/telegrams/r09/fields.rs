@@ -0,0 +1,224 @@
+//!
+//! Typed representations of the small integer fields inside R09 telegrams. The structs
+//! [`R09Telegram`] and [`R09SaveTelegram`] keep storing plain integers, the enums can be
+//! retrieved with the accessor methods.
+//!
+//! All enums are (de)serialized like [`R09Type`][super::R09Type]: serialization produces the
+//! number, deserialization accepts either the number or the name of the variant.
+//!
+
+use super::{R09SaveTelegram, R09Telegram, RequestStatus};
+
+use diesel::deserialize::{self, FromSql};
+use diesel::pg::Pg;
+use diesel::serialize::{self, Output, ToSql};
+use diesel::{AsExpression, FromSqlRow};
+
+use std::fmt;
+
+/// Priority the vehicle requests at the traffic light.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, AsExpression, FromSqlRow)]
+#[diesel(sql_type = diesel::sql_types::SmallInt)]
+pub enum Priority {
+    /// Vehicle doesn't request priority.
+    NoPriority = 0,
+    /// Low priority.
+    Low = 1,
+    /// Medium priority.
+    Medium = 2,
+    /// High priority e.g. for vehicles with a big delay.
+    High = 3,
+}
+
+/// Direction the driver requested manually (ger. Handrichtungsanforderung).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, AsExpression, FromSqlRow)]
+#[diesel(sql_type = diesel::sql_types::SmallInt)]
+pub enum DirectionRequest {
+    /// No direction was requested.
+    NoRequest = 0,
+    /// Vehicle wants to go straight.
+    Straight = 1,
+    /// Vehicle wants to turn left.
+    Left = 2,
+    /// Vehicle wants to turn right.
+    Right = 3,
+}
+
+/// Error returned when an integer doesn't correspond to any variant of a R09 field enum.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidValueError {
+    /// name of the field
+    pub field: &'static str,
+    /// offending value
+    pub value: i64,
+}
+
+impl fmt::Display for InvalidValueError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "no {} corresponding to {}", self.field, self.value)
+    }
+}
+
+impl std::error::Error for InvalidValueError {}
+
+/// Implements integer conversions, serde and diesel `SmallInt` support for a field enum.
+macro_rules! r09_field_enum {
+    ($name:ident, $field:literal, { $($variant:ident = $value:literal),+ $(,)? }) => {
+        impl TryFrom<i16> for $name {
+            type Error = InvalidValueError;
+            fn try_from(value: i16) -> Result<Self, Self::Error> {
+                match value {
+                    $($value => Ok($name::$variant),)+
+                    _ => Err(InvalidValueError {
+                        field: $field,
+                        value: value.into(),
+                    }),
+                }
+            }
+        }
+
+        impl From<$name> for i16 {
+            fn from(value: $name) -> Self {
+                match value {
+                    $($name::$variant => $value,)+
+                }
+            }
+        }
+
+        impl serde::Serialize for $name {
+            fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: serde::Serializer,
+            {
+                serializer.serialize_i16((*self).into())
+            }
+        }
+
+        impl<'de> serde::Deserialize<'de> for $name {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: serde::Deserializer<'de>,
+            {
+                struct FieldVisitor;
+
+                impl serde::de::Visitor<'_> for FieldVisitor {
+                    type Value = $name;
+
+                    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                        write!(
+                            formatter,
+                            concat!("an integer or string representing a ", stringify!($name))
+                        )
+                    }
+
+                    fn visit_str<E: serde::de::Error>(self, s: &str) -> Result<$name, E> {
+                        match s {
+                            $(stringify!($variant) => Ok($name::$variant),)+
+                            _ => Err(E::invalid_value(serde::de::Unexpected::Str(s), &self)),
+                        }
+                    }
+
+                    fn visit_i64<E: serde::de::Error>(self, n: i64) -> Result<$name, E> {
+                        i16::try_from(n)
+                            .ok()
+                            .and_then(|value| $name::try_from(value).ok())
+                            .ok_or_else(|| {
+                                E::invalid_value(serde::de::Unexpected::Signed(n), &self)
+                            })
+                    }
+
+                    fn visit_u64<E: serde::de::Error>(self, n: u64) -> Result<$name, E> {
+                        i16::try_from(n)
+                            .ok()
+                            .and_then(|value| $name::try_from(value).ok())
+                            .ok_or_else(|| {
+                                E::invalid_value(serde::de::Unexpected::Unsigned(n), &self)
+                            })
+                    }
+                }
+
+                deserializer.deserialize_any(FieldVisitor)
+            }
+        }
+
+        impl FromSql<diesel::sql_types::SmallInt, Pg> for $name {
+            fn from_sql(
+                bytes: <Pg as diesel::backend::Backend>::RawValue<'_>,
+            ) -> deserialize::Result<Self> {
+                let v: i16 = i16::from_sql(bytes)?;
+                let res: $name = v.try_into()?;
+                Ok(res)
+            }
+        }
+
+        impl ToSql<diesel::sql_types::SmallInt, Pg> for $name {
+            fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+                match self {
+                    $($name::$variant => {
+                        <i16 as ToSql<diesel::sql_types::SmallInt, Pg>>::to_sql(&$value, out)
+                    })+
+                }
+            }
+        }
+    };
+}
+
+r09_field_enum!(RequestStatus, "request status", {
+    PreRegistration = 0,
+    Registration = 1,
+    DeRegistration = 2,
+    DoorClosed = 3,
+});
+
+r09_field_enum!(Priority, "priority", {
+    NoPriority = 0,
+    Low = 1,
+    Medium = 2,
+    High = 3,
+});
+
+r09_field_enum!(DirectionRequest, "direction request", {
+    NoRequest = 0,
+    Straight = 1,
+    Left = 2,
+    Right = 3,
+});
+
+impl R09Telegram {
+    /// Returns `request_status` as [`RequestStatus`] or [`None`] if the value is invalid.
+    pub fn get_request_status(&self) -> Option<RequestStatus> {
+        RequestStatus::try_from(i16::from(self.request_status)).ok()
+    }
+
+    /// Returns `priority` as [`Priority`] or [`None`] if the value is missing or invalid.
+    pub fn get_priority(&self) -> Option<Priority> {
+        self.priority
+            .and_then(|x| Priority::try_from(i16::from(x)).ok())
+    }
+
+    /// Returns `direction_request` as [`DirectionRequest`] or [`None`] if the value is missing or
+    /// invalid.
+    pub fn get_direction_request(&self) -> Option<DirectionRequest> {
+        self.direction_request
+            .and_then(|x| DirectionRequest::try_from(i16::from(x)).ok())
+    }
+}
+
+impl R09SaveTelegram {
+    /// Returns `request_status` as [`RequestStatus`] or [`None`] if the value is invalid.
+    pub fn get_request_status(&self) -> Option<RequestStatus> {
+        RequestStatus::try_from(self.request_status).ok()
+    }
+
+    /// Returns `priority` as [`Priority`] or [`None`] if the value is missing or invalid.
+    pub fn get_priority(&self) -> Option<Priority> {
+        self.priority.and_then(|x| Priority::try_from(x).ok())
+    }
+
+    /// Returns `direction_request` as [`DirectionRequest`] or [`None`] if the value is missing or
+    /// invalid.
+    pub fn get_direction_request(&self) -> Option<DirectionRequest> {
+        self.direction_request
+            .and_then(|x| DirectionRequest::try_from(x).ok())
+    }
+}
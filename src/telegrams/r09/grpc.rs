@@ -0,0 +1,178 @@
+//!
+//! Conversions between the R09 telegram structs and the [`R09GrpcTelegram`] exchanged between
+//! services.
+//!
+
+use super::{
+    R09ConversionError, R09SaveTelegram, R09Telegram, R09Type, clamp, convert, convert_option,
+};
+use crate::grpc::R09GrpcTelegram;
+use crate::telegrams::TelegramMetaInformation;
+
+use crate::timestamp::Timestamp;
+
+use log::info;
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+use uuid::Uuid;
+
+impl Serialize for R09GrpcTelegram {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut s = serializer.serialize_struct("R09GrpcTelegram", 17)?;
+
+        s.serialize_field("time", &self.time)?;
+        s.serialize_field("station", &self.station)?;
+        s.serialize_field("region", &self.region)?;
+        s.serialize_field("r09_type", &self.r09_type)?;
+
+        self.delay
+            .map(|value| s.serialize_field("delay", &value).ok());
+
+        s.serialize_field("reporting_point", &self.reporting_point)?;
+        s.serialize_field("junction", &self.junction)?;
+        s.serialize_field("direction", &self.direction)?;
+        s.serialize_field("request_status", &self.request_status)?;
+
+        self.priority
+            .map(|value| s.serialize_field("priority", &value).ok());
+        self.direction_request
+            .map(|value| s.serialize_field("direction_request", &value).ok());
+        self.line
+            .map(|value| s.serialize_field("line", &value).ok());
+        self.run_number
+            .map(|value| s.serialize_field("run_number", &value).ok());
+        self.destination_number
+            .map(|value| s.serialize_field("destination_number", &value).ok());
+        self.train_length
+            .map(|value| s.serialize_field("train_length", &value).ok());
+        self.vehicle_number
+            .map(|value| s.serialize_field("vehicle_number", &value).ok());
+        self.operator
+            .map(|value| s.serialize_field("operator", &value).ok());
+
+        s.end()
+    }
+}
+
+impl R09GrpcTelegram {
+    /// Creates a R09GrpcTelegram from a raw R09Telegram and Meta Information. Values which don't
+    /// fit into the message are clamped to `i32::MAX`, time stamps before 1970 are sent as `0`.
+    #[deprecated(
+        since = "0.10.0",
+        note = "Please use TryFrom<(R09Telegram, TelegramMetaInformation)> trait from now on!"
+    )]
+    pub fn create(telegram: R09Telegram, meta: TelegramMetaInformation) -> R09GrpcTelegram {
+        let unix_timestamp = u64::try_from(Timestamp::from(meta.time).as_millis()).unwrap_or(0);
+        info!("going from {:?} to {}", &meta.time, &unix_timestamp);
+        R09GrpcTelegram {
+            time: unix_timestamp,
+            station: meta.station.to_string(),
+            region: meta.region,
+
+            r09_type: telegram.r09_type as i32,
+            delay: telegram.delay,
+            reporting_point: clamp(telegram.reporting_point),
+            junction: clamp(telegram.junction),
+            direction: telegram.direction.into(),
+            request_status: telegram.request_status.into(),
+            priority: telegram.priority.map(i32::from),
+            direction_request: telegram.direction_request.map(i32::from),
+            line: telegram.line.map(clamp),
+            run_number: telegram.run_number.map(clamp),
+            destination_number: telegram.destination_number.map(clamp),
+            train_length: telegram.train_length,
+            vehicle_number: telegram.vehicle_number.map(clamp),
+            operator: telegram.operator.map(i32::from),
+        }
+    }
+}
+
+impl TryFrom<(R09Telegram, TelegramMetaInformation)> for R09GrpcTelegram {
+    type Error = R09ConversionError;
+
+    fn try_from(
+        (telegram, meta): (R09Telegram, TelegramMetaInformation),
+    ) -> Result<Self, Self::Error> {
+        Ok(R09GrpcTelegram {
+            time: convert("time", Timestamp::from(meta.time).as_millis())?,
+            station: meta.station.to_string(),
+            region: meta.region,
+
+            r09_type: telegram.r09_type as i32,
+            delay: telegram.delay,
+            reporting_point: convert("reporting_point", telegram.reporting_point)?,
+            junction: convert("junction", telegram.junction)?,
+            direction: telegram.direction.into(),
+            request_status: telegram.request_status.into(),
+            priority: telegram.priority.map(i32::from),
+            direction_request: telegram.direction_request.map(i32::from),
+            line: convert_option("line", telegram.line)?,
+            run_number: convert_option("run_number", telegram.run_number)?,
+            destination_number: convert_option("destination_number", telegram.destination_number)?,
+            train_length: telegram.train_length,
+            vehicle_number: convert_option("vehicle_number", telegram.vehicle_number)?,
+            operator: telegram.operator.map(i32::from),
+        })
+    }
+}
+
+impl TryFrom<R09GrpcTelegram> for R09Telegram {
+    type Error = R09ConversionError;
+
+    fn try_from(telegram: R09GrpcTelegram) -> Result<Self, Self::Error> {
+        Ok(R09Telegram {
+            r09_type: R09Type::try_from(i64::from(telegram.r09_type))
+                .map_err(|_| R09ConversionError::UnknownR09Type(telegram.r09_type.into()))?,
+            delay: telegram.delay,
+            reporting_point: convert("reporting_point", telegram.reporting_point)?,
+            junction: convert("junction", telegram.junction)?,
+            direction: convert("direction", telegram.direction)?,
+            request_status: convert("request_status", telegram.request_status)?,
+            priority: convert_option("priority", telegram.priority)?,
+            direction_request: convert_option("direction_request", telegram.direction_request)?,
+            line: convert_option("line", telegram.line)?,
+            run_number: convert_option("run_number", telegram.run_number)?,
+            destination_number: convert_option("destination_number", telegram.destination_number)?,
+            train_length: telegram.train_length,
+            vehicle_number: convert_option("vehicle_number", telegram.vehicle_number)?,
+            operator: convert_option("operator", telegram.operator)?,
+        })
+    }
+}
+
+impl TryFrom<R09GrpcTelegram> for R09SaveTelegram {
+    type Error = R09ConversionError;
+
+    fn try_from(telegram: R09GrpcTelegram) -> Result<Self, Self::Error> {
+        let time = telegram
+            .get_time()
+            .map_err(|_| R09ConversionError::InvalidTime(telegram.time))?
+            .naive_utc();
+
+        Ok(R09SaveTelegram {
+            id: None,
+
+            time,
+            station: Uuid::parse_str(&telegram.station)
+                .map_err(R09ConversionError::InvalidStation)?,
+            r09_type: R09Type::try_from(i64::from(telegram.r09_type))
+                .map_err(|_| R09ConversionError::UnknownR09Type(telegram.r09_type.into()))?,
+            delay: telegram.delay,
+            reporting_point: telegram.reporting_point,
+            junction: telegram.junction,
+            direction: convert("direction", telegram.direction)?,
+            request_status: convert("request_status", telegram.request_status)?,
+            priority: convert_option("priority", telegram.priority)?,
+            direction_request: convert_option("direction_request", telegram.direction_request)?,
+            line: telegram.line,
+            run_number: telegram.run_number,
+            destination_number: telegram.destination_number,
+            train_length: telegram.train_length,
+            vehicle_number: telegram.vehicle_number,
+            operator: convert_option("operator", telegram.operator)?,
+            region: telegram.region,
+        })
+    }
+}
@@ -2,6 +2,17 @@
 //! This module contains structs, exchange formats and implementations for R09 Telegrams.
 //!
 
+pub mod codec;
+pub mod fields;
+pub mod grpc;
+pub mod reporting_point;
+#[cfg(test)]
+mod tests;
+
+pub use codec::{R09DecodeError, R09EncodeError, decode};
+pub use fields::{DirectionRequest, InvalidValueError, Priority};
+pub use reporting_point::{ReportingPoint, ReportingPointError, RequestStatus};
+
 use crate::management::Station;
 use crate::schema::r09_telegrams;
 use crate::telegrams::{
@@ -13,8 +24,6 @@ use diesel::deserialize::{self, FromSql};
 use diesel::pg::Pg;
 use diesel::serialize::{self, Output, ToSql};
 use diesel::{AsExpression, Associations, FromSqlRow, Identifiable, Insertable, Queryable};
-use log::info;
-use serde::ser::{SerializeStruct, Serializer};
 use serde::{Deserialize, Serialize};
 use struct_field_names_as_array::FieldNamesAsArray;
 use utoipa::ToSchema;
@@ -24,13 +33,11 @@ use std::fmt;
 use std::hash::Hash;
 use std::hash::Hasher;
 
-use crate::grpc::R09GrpcTelegram;
-
 /// The R09Telegram is the heart piece it hold the raw information from the received
 /// radio-telegram. The goal was of this struct is to be the smallest denominator
 /// of all different telegram formats (**R09.14**, **R09.16**, **R09.18**).
 ///
-#[derive(Deserialize, Serialize, Debug, Clone)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
 pub struct R09Telegram {
     /// standard the telegram follows (**R09.14**, **R09.16**, **R09.18**)
     pub r09_type: R09Type,
@@ -43,12 +50,12 @@ pub struct R09Telegram {
     pub junction: u32,
     /// Which direction the vehicle wants to drive.
     pub direction: u8,
-    /// Enum in which state of registration this vehicle is see
-    /// [`RequestStatus`][crate::locations::RequestStatus] for more information.
+    /// Enum in which state of registration this vehicle is see [`RequestStatus`] for more
+    /// information.
     pub request_status: u8,
-    /// If the vehicle requests priority or not.
+    /// If the vehicle requests priority or not, see [`Priority`].
     pub priority: Option<u8>,
-    /// TODO: marenz I am not standing up to get the paper now NOO !
+    /// Direction the driver requested manually, see [`DirectionRequest`].
     pub direction_request: Option<u8>,
     /// Line (ger. linie) of the vehicle.
     pub line: Option<u32>,
@@ -106,12 +113,12 @@ pub struct R09SaveTelegram {
     pub junction: i32,
     /// Which direction the vehicle wants to drive.
     pub direction: i16,
-    /// Enum in which state of registration this vehicle is see
-    /// [RequestStatus][crate::locations::RequestStatus] for more information.
+    /// Enum in which state of registration this vehicle is see [`RequestStatus`] for more
+    /// information.
     pub request_status: i16,
-    /// If the vehicle requests priority or not.
+    /// If the vehicle requests priority or not, see [`Priority`].
     pub priority: Option<i16>,
-    /// TODO: marenz I am not standing up to get the paper now NOO !
+    /// Direction the driver requested manually, see [`DirectionRequest`].
     pub direction_request: Option<i16>,
     /// Line (ger. linie) of the vehicle.
     pub line: Option<i32>,
@@ -233,7 +240,9 @@ impl TryFrom<i64> for R09Type {
 }
 
 impl FromSql<diesel::sql_types::BigInt, Pg> for R09Type {
-    fn from_sql(bytes: diesel::backend::RawValue<'_, Pg>) -> deserialize::Result<Self> {
+    fn from_sql(
+        bytes: <Pg as diesel::backend::Backend>::RawValue<'_>,
+    ) -> deserialize::Result<Self> {
         //<R09Type as deserialize::FromSql<diesel::sql_types::BigInt, Pg>>::from_sql(bytes).map(|i| R09Type::from(i))
         let v: i64 = i64::from_sql(bytes)?;
         let res: R09Type = v.try_into()?;
@@ -267,6 +276,70 @@ impl Hash for R09Type {
     }
 }
 
+/// Error returned when converting between the R09 telegram representations fails, e.g. from
+/// [`R09GrpcTelegram`][crate::grpc::R09GrpcTelegram] into [`R09Telegram`] or from [`R09Telegram`]
+/// into [`R09SaveTelegram`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum R09ConversionError {
+    /// The station is not a valid UUID.
+    InvalidStation(uuid::Error),
+    /// The `r09_type` doesn't correspond to any [`R09Type`].
+    UnknownR09Type(i64),
+    /// The timestamp can not be represented as [`NaiveDateTime`].
+    InvalidTime(u64),
+    /// The value doesn't fit into the target field.
+    OutOfRange {
+        /// name of the field which failed to convert
+        field: &'static str,
+        /// offending value
+        value: i64,
+    },
+}
+
+impl fmt::Display for R09ConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            R09ConversionError::InvalidStation(e) => write!(f, "invalid station uuid: {e}"),
+            R09ConversionError::UnknownR09Type(value) => write!(f, "no such R09 type: {value}"),
+            R09ConversionError::InvalidTime(value) => write!(f, "invalid timestamp: {value}"),
+            R09ConversionError::OutOfRange { field, value } => {
+                write!(f, "value {value} is out of range for field {field}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for R09ConversionError {}
+
+/// Converts an integer into the integer type of the target field.
+fn convert<T, V>(field: &'static str, value: V) -> Result<T, R09ConversionError>
+where
+    T: TryFrom<V>,
+    V: Into<i64> + Copy,
+{
+    T::try_from(value).map_err(|_| R09ConversionError::OutOfRange {
+        field,
+        value: value.into(),
+    })
+}
+
+/// Converts an optional integer into the integer type of the target field.
+fn convert_option<T, V>(
+    field: &'static str,
+    value: Option<V>,
+) -> Result<Option<T>, R09ConversionError>
+where
+    T: TryFrom<V>,
+    V: Into<i64> + Copy,
+{
+    value.map(|x| convert(field, x)).transpose()
+}
+
+/// Converts an integer into an `i32` field, values which don't fit are clamped to `i32::MAX`.
+fn clamp(value: u32) -> i32 {
+    i32::try_from(value).unwrap_or(i32::MAX)
+}
+
 impl GetTelegramType for R09Telegram {
     fn get_type(&self) -> TelegramType {
         TelegramType::R09
@@ -275,7 +348,12 @@ impl GetTelegramType for R09Telegram {
 
 impl R09SaveTelegram {
     /// Takes a raw R09Telegram and Meta data to create a R09SaveTelegram which then can be written
-    /// to the database.
+    /// to the database. Values which don't fit into the database columns are clamped to
+    /// `i32::MAX`.
+    #[deprecated(
+        since = "0.10.0",
+        note = "Please use TryFrom<(R09Telegram, TelegramMetaInformation)> trait from now on!"
+    )]
     pub fn from(telegram: R09Telegram, meta: TelegramMetaInformation) -> R09SaveTelegram {
         R09SaveTelegram {
             id: None,
@@ -284,23 +362,55 @@ impl R09SaveTelegram {
             station: meta.station,
             r09_type: telegram.r09_type,
             delay: telegram.delay,
-            reporting_point: telegram.reporting_point as i32,
-            junction: telegram.junction as i32,
-            direction: telegram.direction as i16,
-            request_status: telegram.request_status as i16,
-            priority: telegram.priority.map(|x| x as i16),
-            direction_request: telegram.direction_request.map(|x| x as i16),
-            line: telegram.line.map(|x| x as i32),
-            run_number: telegram.run_number.map(|x| x as i32),
-            destination_number: telegram.destination_number.map(|x| x as i32),
+            reporting_point: clamp(telegram.reporting_point),
+            junction: clamp(telegram.junction),
+            direction: telegram.direction.into(),
+            request_status: telegram.request_status.into(),
+            priority: telegram.priority.map(i16::from),
+            direction_request: telegram.direction_request.map(i16::from),
+            line: telegram.line.map(clamp),
+            run_number: telegram.run_number.map(clamp),
+            destination_number: telegram.destination_number.map(clamp),
             train_length: telegram.train_length,
-            vehicle_number: telegram.vehicle_number.map(|x| x as i32),
-            operator: telegram.operator.map(|x| x as i16),
+            vehicle_number: telegram.vehicle_number.map(clamp),
+            operator: telegram.operator.map(i16::from),
             region: meta.region,
         }
     }
 }
 
+impl TryFrom<(R09Telegram, TelegramMetaInformation)> for R09SaveTelegram {
+    type Error = R09ConversionError;
+
+    /// Takes a raw R09Telegram and Meta data to create a R09SaveTelegram which then can be written
+    /// to the database. Fails if a value doesn't fit into its database column.
+    fn try_from(
+        (telegram, meta): (R09Telegram, TelegramMetaInformation),
+    ) -> Result<Self, Self::Error> {
+        Ok(R09SaveTelegram {
+            id: None,
+
+            time: meta.time,
+            station: meta.station,
+            r09_type: telegram.r09_type,
+            delay: telegram.delay,
+            reporting_point: convert("reporting_point", telegram.reporting_point)?,
+            junction: convert("junction", telegram.junction)?,
+            direction: telegram.direction.into(),
+            request_status: telegram.request_status.into(),
+            priority: telegram.priority.map(i16::from),
+            direction_request: telegram.direction_request.map(i16::from),
+            line: convert_option("line", telegram.line)?,
+            run_number: convert_option("run_number", telegram.run_number)?,
+            destination_number: convert_option("destination_number", telegram.destination_number)?,
+            train_length: telegram.train_length,
+            vehicle_number: convert_option("vehicle_number", telegram.vehicle_number)?,
+            operator: telegram.operator.map(i16::from),
+            region: meta.region,
+        })
+    }
+}
+
 impl Hash for R09ReceiveTelegram {
     fn hash<H: Hasher>(&self, state: &mut H) {
         self.data.hash(state);
@@ -335,72 +445,3 @@ impl fmt::Display for R09Telegram {
         )
     }
 }
-
-impl Serialize for R09GrpcTelegram {
-    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
-    where
-        S: Serializer,
-    {
-        let mut s = serializer.serialize_struct("R09GrpcTelegram", 17)?;
-
-        s.serialize_field("time", &self.time)?;
-        s.serialize_field("station", &self.station)?;
-        s.serialize_field("region", &self.region)?;
-        s.serialize_field("r09_type", &self.r09_type)?;
-
-        self.delay
-            .map(|value| s.serialize_field("delay", &value).ok());
-
-        s.serialize_field("reporting_point", &self.reporting_point)?;
-        s.serialize_field("junction", &self.junction)?;
-        s.serialize_field("direction", &self.direction)?;
-        s.serialize_field("request_status", &self.request_status)?;
-
-        self.priority
-            .map(|value| s.serialize_field("priority", &value).ok());
-        self.direction_request
-            .map(|value| s.serialize_field("direction_request", &value).ok());
-        self.line
-            .map(|value| s.serialize_field("line", &value).ok());
-        self.run_number
-            .map(|value| s.serialize_field("run_number", &value).ok());
-        self.destination_number
-            .map(|value| s.serialize_field("destination_number", &value).ok());
-        self.train_length
-            .map(|value| s.serialize_field("train_length", &value).ok());
-        self.vehicle_number
-            .map(|value| s.serialize_field("vehicle_number", &value).ok());
-        self.operator
-            .map(|value| s.serialize_field("operator", &value).ok());
-
-        s.end()
-    }
-}
-
-impl R09GrpcTelegram {
-    /// Creates a R09GrpcTelegram from a raw R09Telegram and Meta Information.
-    pub fn create(telegram: R09Telegram, meta: TelegramMetaInformation) -> R09GrpcTelegram {
-        let unix_timestamp = meta.time.timestamp_millis() as u64;
-        info!("going from {:?} to {}", &meta.time, &unix_timestamp);
-        R09GrpcTelegram {
-            time: unix_timestamp,
-            station: meta.station.to_string(),
-            region: meta.region,
-
-            r09_type: telegram.r09_type as i32,
-            delay: telegram.delay,
-            reporting_point: telegram.reporting_point as i32,
-            junction: telegram.junction as i32,
-            direction: telegram.direction as i32,
-            request_status: telegram.request_status as i32,
-            priority: telegram.priority.map(|x| x as i32),
-            direction_request: telegram.direction_request.map(|x| x as i32),
-            line: telegram.line.map(|x| x as i32),
-            run_number: telegram.run_number.map(|x| x as i32),
-            destination_number: telegram.destination_number.map(|x| x as i32),
-            train_length: telegram.train_length,
-            vehicle_number: telegram.vehicle_number.map(|x| x as i32),
-            operator: telegram.operator.map(|x| x as i32),
-        }
-    }
-}
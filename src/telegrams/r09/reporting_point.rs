@@ -0,0 +1,265 @@
+//!
+//! This module contains the [`ReportingPoint`] (ger. Meldepunkt) and its decomposition into
+//! junction, direction and [`RequestStatus`].
+//!
+//! The 16 bit reporting point is composed of a 12 bit junction number followed by 2 bits for the
+//! direction and 2 bits for the request status.
+//!
+
+use super::{R09SaveTelegram, R09Telegram};
+
+use diesel::{AsExpression, FromSqlRow};
+
+use std::fmt;
+
+/// Number of bits used for the direction inside a reporting point.
+const DIRECTION_BITS: u32 = 2;
+/// Number of bits used for the request status inside a reporting point.
+const REQUEST_STATUS_BITS: u32 = 2;
+
+/// Highest junction number which can be encoded into a reporting point.
+pub const MAX_JUNCTION: u32 = 0x0fff;
+/// Highest direction which can be encoded into a reporting point.
+pub const MAX_DIRECTION: u8 = 0b11;
+
+/// State of registration (ger. Anmeldestatus) a vehicle has at a traffic light.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, AsExpression, FromSqlRow)]
+#[diesel(sql_type = diesel::sql_types::SmallInt)]
+pub enum RequestStatus {
+    /// Vehicle is approaching the junction (ger. Voranmeldung).
+    PreRegistration = 0,
+    /// Vehicle registers at the junction (ger. Hauptanmeldung).
+    Registration = 1,
+    /// Vehicle passed the junction (ger. Abmeldung).
+    DeRegistration = 2,
+    /// Vehicle closed its doors and is ready to depart (ger. Türschließen).
+    DoorClosed = 3,
+}
+
+/// Error for constructing or validating a [`ReportingPoint`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReportingPointError {
+    /// Reporting point doesn't fit into 16 bits.
+    OutOfRange(i64),
+    /// Junction is greater than [`MAX_JUNCTION`].
+    JunctionOutOfRange(i64),
+    /// Direction is greater than [`MAX_DIRECTION`].
+    DirectionOutOfRange(i64),
+    /// Value doesn't correspond to any [`RequestStatus`].
+    InvalidRequestStatus(i64),
+    /// The stored junction, direction and request status don't agree with the reporting point.
+    Mismatch {
+        /// reporting point that was stored
+        reporting_point: ReportingPoint,
+        /// junction that was stored
+        junction: u32,
+        /// direction that was stored
+        direction: u8,
+        /// request status that was stored
+        request_status: u8,
+    },
+}
+
+impl fmt::Display for ReportingPointError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReportingPointError::OutOfRange(value) => {
+                write!(f, "reporting point {value} doesn't fit into 16 bits")
+            }
+            ReportingPointError::JunctionOutOfRange(value) => {
+                write!(f, "junction {value} is greater than {MAX_JUNCTION}")
+            }
+            ReportingPointError::DirectionOutOfRange(value) => {
+                write!(f, "direction {value} is greater than {MAX_DIRECTION}")
+            }
+            ReportingPointError::InvalidRequestStatus(value) => {
+                write!(f, "no request status corresponding to {value}")
+            }
+            ReportingPointError::Mismatch {
+                reporting_point,
+                junction,
+                direction,
+                request_status,
+            } => write!(
+                f,
+                "reporting point {reporting_point} doesn't match junction {junction}, direction \
+                 {direction} and request status {request_status}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ReportingPointError {}
+
+impl TryFrom<u8> for RequestStatus {
+    type Error = ReportingPointError;
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(RequestStatus::PreRegistration),
+            1 => Ok(RequestStatus::Registration),
+            2 => Ok(RequestStatus::DeRegistration),
+            3 => Ok(RequestStatus::DoorClosed),
+            _ => Err(ReportingPointError::InvalidRequestStatus(value.into())),
+        }
+    }
+}
+
+impl From<RequestStatus> for u8 {
+    fn from(value: RequestStatus) -> Self {
+        value as u8
+    }
+}
+
+/// Unique identifier of a location (ger. Meldepunkt) where a R09 telegram is transmitted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ReportingPoint(u16);
+
+impl ReportingPoint {
+    /// Composes a reporting point from its parts.
+    pub fn compose(
+        junction: u32,
+        direction: u8,
+        request_status: RequestStatus,
+    ) -> Result<ReportingPoint, ReportingPointError> {
+        if junction > MAX_JUNCTION {
+            return Err(ReportingPointError::JunctionOutOfRange(junction.into()));
+        }
+        if direction > MAX_DIRECTION {
+            return Err(ReportingPointError::DirectionOutOfRange(direction.into()));
+        }
+
+        let value = (junction << (DIRECTION_BITS + REQUEST_STATUS_BITS))
+            | ((direction as u32) << REQUEST_STATUS_BITS)
+            | request_status as u32;
+
+        Ok(ReportingPoint(value as u16))
+    }
+
+    /// Decomposes the reporting point into junction, direction and request status.
+    pub fn decompose(&self) -> (u32, u8, RequestStatus) {
+        (self.junction(), self.direction(), self.request_status())
+    }
+
+    /// Identifier of the traffic light.
+    pub fn junction(&self) -> u32 {
+        (self.0 >> (DIRECTION_BITS + REQUEST_STATUS_BITS)) as u32
+    }
+
+    /// Direction the vehicle wants to drive.
+    pub fn direction(&self) -> u8 {
+        ((self.0 >> REQUEST_STATUS_BITS) & MAX_DIRECTION as u16) as u8
+    }
+
+    /// State of registration of the vehicle.
+    pub fn request_status(&self) -> RequestStatus {
+        match self.0 & 0b11 {
+            0 => RequestStatus::PreRegistration,
+            1 => RequestStatus::Registration,
+            2 => RequestStatus::DeRegistration,
+            _ => RequestStatus::DoorClosed,
+        }
+    }
+
+    /// Returns the composite value.
+    pub fn value(&self) -> u32 {
+        self.0 as u32
+    }
+
+    /// Checks that junction, direction and request status agree with this reporting point.
+    fn validate(
+        self,
+        junction: u32,
+        direction: u8,
+        request_status: u8,
+    ) -> Result<ReportingPoint, ReportingPointError> {
+        if (
+            self.junction(),
+            self.direction(),
+            self.request_status() as u8,
+        ) != (junction, direction, request_status)
+        {
+            return Err(ReportingPointError::Mismatch {
+                reporting_point: self,
+                junction,
+                direction,
+                request_status,
+            });
+        }
+
+        Ok(self)
+    }
+}
+
+impl From<u16> for ReportingPoint {
+    fn from(value: u16) -> Self {
+        ReportingPoint(value)
+    }
+}
+
+impl TryFrom<u32> for ReportingPoint {
+    type Error = ReportingPointError;
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
+        u16::try_from(value)
+            .map(ReportingPoint)
+            .map_err(|_| ReportingPointError::OutOfRange(value.into()))
+    }
+}
+
+impl From<ReportingPoint> for u32 {
+    fn from(value: ReportingPoint) -> Self {
+        value.value()
+    }
+}
+
+impl fmt::Display for ReportingPoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl R09Telegram {
+    /// Returns the [`ReportingPoint`] after checking that `junction`, `direction` and
+    /// `request_status` agree with the composite `reporting_point`.
+    pub fn get_reporting_point(&self) -> Result<ReportingPoint, ReportingPointError> {
+        ReportingPoint::try_from(self.reporting_point)?.validate(
+            self.junction,
+            self.direction,
+            self.request_status,
+        )
+    }
+
+    /// Sets `reporting_point` and its decomposed parts `junction`, `direction` and
+    /// `request_status`.
+    pub fn set_reporting_point(&mut self, reporting_point: ReportingPoint) {
+        self.reporting_point = reporting_point.value();
+        self.junction = reporting_point.junction();
+        self.direction = reporting_point.direction();
+        self.request_status = reporting_point.request_status().into();
+    }
+}
+
+impl R09SaveTelegram {
+    /// Returns the [`ReportingPoint`] after checking that `junction`, `direction` and
+    /// `request_status` agree with the composite `reporting_point`.
+    pub fn get_reporting_point(&self) -> Result<ReportingPoint, ReportingPointError> {
+        let reporting_point = u32::try_from(self.reporting_point)
+            .map_err(|_| ReportingPointError::OutOfRange(self.reporting_point.into()))?;
+        let junction = u32::try_from(self.junction)
+            .map_err(|_| ReportingPointError::JunctionOutOfRange(self.junction.into()))?;
+        let direction = u8::try_from(self.direction)
+            .map_err(|_| ReportingPointError::DirectionOutOfRange(self.direction.into()))?;
+        let request_status = u8::try_from(self.request_status)
+            .map_err(|_| ReportingPointError::InvalidRequestStatus(self.request_status.into()))?;
+
+        ReportingPoint::try_from(reporting_point)?.validate(junction, direction, request_status)
+    }
+
+    /// Sets `reporting_point` and its decomposed parts `junction`, `direction` and
+    /// `request_status`.
+    pub fn set_reporting_point(&mut self, reporting_point: ReportingPoint) {
+        self.reporting_point = reporting_point.value() as i32;
+        self.junction = reporting_point.junction() as i32;
+        self.direction = reporting_point.direction().into();
+        self.request_status = (reporting_point.request_status() as u8).into();
+    }
+}
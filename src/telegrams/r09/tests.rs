@@ -0,0 +1,408 @@
+use super::*;
+use crate::grpc::R09GrpcTelegram;
+use crate::telegrams::TelegramType;
+use crate::telegrams::raw::RawTelegram;
+
+// R09.16: delay -3 min, train length 2, reporting point 0x1a2b, priority 1, direction request 2,
+// line 085, run 12, destination 304, vehicle 2217
+const R16_FRAME: [u8; 11] = [
+    0x9a, 0xda, 0x1a, 0x2b, 0x60, 0x85, 0x12, 0x30, 0x40, 0x22, 0x17,
+];
+
+#[test]
+fn test_decode_r16() {
+    let telegram = decode(&R16_FRAME, R09Type::R16).expect("cannot decode telegram!");
+
+    assert_eq!(
+        telegram,
+        R09Telegram {
+            r09_type: R09Type::R16,
+            delay: Some(-3),
+            reporting_point: 0x1a2b,
+            junction: 0x1a2,
+            direction: 2,
+            request_status: 3,
+            priority: Some(1),
+            direction_request: Some(2),
+            line: Some(85),
+            run_number: Some(12),
+            destination_number: Some(304),
+            train_length: Some(2),
+            vehicle_number: Some(2217),
+            operator: None,
+        }
+    );
+}
+
+#[test]
+fn test_decode_raw_telegram() {
+    let raw = RawTelegram {
+        telegram_type: TelegramType::R09,
+        data: R16_FRAME.to_vec(),
+    };
+    let telegram = R09Telegram::try_from(&raw).expect("cannot decode raw telegram!");
+    assert_eq!(telegram.r09_type, R09Type::R16);
+
+    let not_r09 = RawTelegram {
+        telegram_type: TelegramType::R05,
+        data: R16_FRAME.to_vec(),
+    };
+    assert_eq!(
+        R09Telegram::try_from(&not_r09),
+        Err(R09DecodeError::NotR09(TelegramType::R05))
+    );
+}
+
+#[test]
+fn test_decode_errors() {
+    assert_eq!(
+        decode(&[], R09Type::R14),
+        Err(R09DecodeError::Truncated {
+            expected: 1,
+            actual: 0
+        })
+    );
+    assert_eq!(
+        decode(&R16_FRAME[..5], R09Type::R16),
+        Err(R09DecodeError::Truncated {
+            expected: 11,
+            actual: 5
+        })
+    );
+    assert_eq!(
+        decode(&R16_FRAME, R09Type::R18),
+        Err(R09DecodeError::TypeMismatch {
+            expected: R09Type::R18,
+            found: R09Type::R16
+        })
+    );
+    assert_eq!(
+        decode(&[0x5a], R09Type::R16),
+        Err(R09DecodeError::InvalidMode(5))
+    );
+
+    let mut frame = R16_FRAME;
+    frame[5] = 0x8c;
+    assert_eq!(
+        decode(&frame, R09Type::R16),
+        Err(R09DecodeError::InvalidBcd {
+            field: "line",
+            nibble: 0xc
+        })
+    );
+}
+
+/// Small xorshift generator, so the round trip tests are deterministic without extra dependencies.
+struct Xorshift(u64);
+
+impl Xorshift {
+    fn below(&mut self, bound: u32) -> u32 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 % bound as u64) as u32
+    }
+}
+
+fn random_telegram(rng: &mut Xorshift) -> R09Telegram {
+    let r09_type = match rng.below(3) {
+        0 => R09Type::R14,
+        1 => R09Type::R16,
+        _ => R09Type::R18,
+    };
+    let reporting_point = rng.below(0x10000);
+    let delay = match rng.below(2) {
+        0 => None,
+        _ => Some(rng.below(15) as i32 - 7),
+    };
+
+    R09Telegram {
+        delay,
+        reporting_point,
+        junction: reporting_point >> 4,
+        direction: ((reporting_point >> 2) & 0b11) as u8,
+        request_status: (reporting_point & 0b11) as u8,
+        priority: Some(rng.below(4) as u8),
+        direction_request: Some(rng.below(4) as u8),
+        line: Some(rng.below(1000)),
+        run_number: Some(rng.below(100)),
+        destination_number: Some(rng.below(1000)),
+        train_length: Some(rng.below(8) as i32),
+        vehicle_number: match r09_type {
+            R09Type::R14 => None,
+            _ => Some(rng.below(10000)),
+        },
+        operator: match r09_type {
+            R09Type::R18 => Some(rng.below(100) as u8),
+            _ => None,
+        },
+        r09_type,
+    }
+}
+
+#[test]
+fn test_encode_r16() {
+    let telegram = decode(&R16_FRAME, R09Type::R16).expect("cannot decode telegram!");
+    assert_eq!(telegram.encode(), R16_FRAME.to_vec());
+}
+
+#[test]
+fn test_encode_decode_round_trip() {
+    let mut rng = Xorshift(0x2545_f491_4f6c_dd1d);
+
+    for _ in 0..10_000 {
+        let telegram = random_telegram(&mut rng);
+        let encoded = telegram.try_encode().expect("cannot encode telegram!");
+        let decoded = decode(&encoded, telegram.r09_type.clone()).expect("cannot decode telegram!");
+        assert_eq!(decoded, telegram);
+
+        // every value the bitstream can carry has to fit into the database representation
+        let meta = crate::telegrams::TelegramMetaInformation {
+            time: chrono::NaiveDateTime::default(),
+            station: uuid::Uuid::nil(),
+            region: 0,
+        };
+        let save =
+            R09SaveTelegram::try_from((decoded, meta)).expect("cannot convert to save telegram!");
+        assert_eq!(save.reporting_point as u32, telegram.reporting_point);
+        assert_eq!(save.junction as u32, telegram.junction);
+        assert_eq!(save.line.map(|x| x as u32), telegram.line);
+        assert_eq!(
+            save.vehicle_number.map(|x| x as u32),
+            telegram.vehicle_number
+        );
+        assert_eq!(save.operator.map(|x| x as u8), telegram.operator);
+    }
+}
+
+#[test]
+fn test_encode_out_of_range() {
+    let mut telegram = decode(&R16_FRAME, R09Type::R16).expect("cannot decode telegram!");
+    telegram.line = Some(1234);
+
+    assert_eq!(
+        telegram.try_encode(),
+        Err(R09EncodeError::OutOfRange {
+            field: "line",
+            value: 1234
+        })
+    );
+}
+
+#[test]
+fn test_reporting_point_compose_decompose() {
+    let reporting_point = ReportingPoint::compose(0x1a2, 2, RequestStatus::DoorClosed)
+        .expect("cannot compose reporting point!");
+    assert_eq!(reporting_point.value(), 0x1a2b);
+    assert_eq!(
+        reporting_point.decompose(),
+        (0x1a2, 2, RequestStatus::DoorClosed)
+    );
+
+    assert_eq!(
+        ReportingPoint::compose(0x1000, 0, RequestStatus::Registration),
+        Err(ReportingPointError::JunctionOutOfRange(0x1000))
+    );
+    assert_eq!(
+        ReportingPoint::try_from(0x10000_u32),
+        Err(ReportingPointError::OutOfRange(0x10000))
+    );
+}
+
+#[test]
+fn test_reporting_point_validation() {
+    let mut telegram = decode(&R16_FRAME, R09Type::R16).expect("cannot decode telegram!");
+    assert_eq!(
+        telegram.get_reporting_point(),
+        Ok(ReportingPoint::from(0x1a2b))
+    );
+
+    telegram.direction = 1;
+    assert!(matches!(
+        telegram.get_reporting_point(),
+        Err(ReportingPointError::Mismatch { direction: 1, .. })
+    ));
+
+    telegram.set_reporting_point(ReportingPoint::from(0x0011));
+    assert_eq!(telegram.junction, 1);
+    assert_eq!(telegram.direction, 0);
+    assert_eq!(telegram.request_status, RequestStatus::Registration as u8);
+    assert!(telegram.get_reporting_point().is_ok());
+}
+
+#[test]
+fn test_field_enums() {
+    let telegram = decode(&R16_FRAME, R09Type::R16).expect("cannot decode telegram!");
+    assert_eq!(
+        telegram.get_request_status(),
+        Some(RequestStatus::DoorClosed)
+    );
+    assert_eq!(telegram.get_priority(), Some(Priority::Low));
+    assert_eq!(
+        telegram.get_direction_request(),
+        Some(DirectionRequest::Left)
+    );
+
+    assert_eq!(
+        serde_json::to_string(&Priority::High).expect("cannot serialize priority!"),
+        "3"
+    );
+    assert_eq!(
+        serde_json::from_str::<DirectionRequest>("\"Right\"").expect("cannot deserialize!"),
+        DirectionRequest::Right
+    );
+    assert_eq!(
+        serde_json::from_str::<RequestStatus>("1").expect("cannot deserialize!"),
+        RequestStatus::Registration
+    );
+    assert!(serde_json::from_str::<Priority>("4").is_err());
+    assert_eq!(
+        Priority::try_from(-1_i16),
+        Err(InvalidValueError {
+            field: "priority",
+            value: -1
+        })
+    );
+}
+
+#[test]
+fn test_grpc_round_trip() {
+    let telegram = decode(&R16_FRAME, R09Type::R16).expect("cannot decode telegram!");
+    let meta = crate::telegrams::TelegramMetaInformation {
+        time: chrono::DateTime::from_timestamp_millis(1_681_000_000_123)
+            .expect("invalid timestamp!")
+            .naive_utc(),
+        station: uuid::Uuid::from_u128(0x53e643d7_c300_4de7_ab48_540d08a0cbc6),
+        region: 1,
+    };
+
+    let grpc = R09GrpcTelegram::try_from((telegram.clone(), meta.clone()))
+        .expect("cannot convert to grpc telegram!");
+
+    let save = R09SaveTelegram::try_from(grpc.clone()).expect("cannot convert to save telegram!");
+    assert_eq!(
+        Ok(save),
+        R09SaveTelegram::try_from((telegram.clone(), meta))
+    );
+
+    let converted = R09Telegram::try_from(grpc).expect("cannot convert to telegram!");
+    assert_eq!(converted, telegram);
+}
+
+#[test]
+fn test_grpc_conversion_errors() {
+    let telegram = decode(&R16_FRAME, R09Type::R16).expect("cannot decode telegram!");
+    let meta = crate::telegrams::TelegramMetaInformation {
+        time: chrono::NaiveDateTime::default(),
+        station: uuid::Uuid::nil(),
+        region: 1,
+    };
+    let grpc =
+        R09GrpcTelegram::try_from((telegram, meta)).expect("cannot convert to grpc telegram!");
+
+    let mut invalid = grpc.clone();
+    invalid.station = "not a uuid".to_string();
+    assert!(matches!(
+        R09SaveTelegram::try_from(invalid),
+        Err(R09ConversionError::InvalidStation(_))
+    ));
+
+    let mut invalid = grpc.clone();
+    invalid.r09_type = 15;
+    assert_eq!(
+        R09Telegram::try_from(invalid),
+        Err(R09ConversionError::UnknownR09Type(15))
+    );
+
+    let mut invalid = grpc;
+    invalid.direction = 70_000;
+    assert_eq!(
+        R09SaveTelegram::try_from(invalid.clone()),
+        Err(R09ConversionError::OutOfRange {
+            field: "direction",
+            value: 70_000
+        })
+    );
+    assert_eq!(
+        R09Telegram::try_from(invalid),
+        Err(R09ConversionError::OutOfRange {
+            field: "direction",
+            value: 70_000
+        })
+    );
+}
+
+#[test]
+fn test_checked_conversion_overflow() {
+    let telegram = decode(&R16_FRAME, R09Type::R16).expect("cannot decode telegram!");
+    let meta = crate::telegrams::TelegramMetaInformation {
+        time: chrono::NaiveDateTime::default(),
+        station: uuid::Uuid::nil(),
+        region: 1,
+    };
+
+    let mut invalid = telegram.clone();
+    invalid.reporting_point = u32::MAX;
+    let expected = Some(R09ConversionError::OutOfRange {
+        field: "reporting_point",
+        value: u32::MAX.into(),
+    });
+    assert_eq!(
+        R09SaveTelegram::try_from((invalid.clone(), meta.clone())).err(),
+        expected
+    );
+    assert_eq!(
+        R09GrpcTelegram::try_from((invalid, meta.clone())).err(),
+        expected
+    );
+
+    let mut invalid = telegram.clone();
+    invalid.vehicle_number = Some(1 << 31);
+    assert_eq!(
+        R09SaveTelegram::try_from((invalid, meta.clone())),
+        Err(R09ConversionError::OutOfRange {
+            field: "vehicle_number",
+            value: 1 << 31,
+        })
+    );
+
+    // timestamps before the epoch can not be sent via grpc
+    let before_epoch = crate::telegrams::TelegramMetaInformation {
+        time: chrono::DateTime::from_timestamp_millis(-1)
+            .expect("invalid timestamp!")
+            .naive_utc(),
+        ..meta
+    };
+    assert_eq!(
+        R09GrpcTelegram::try_from((telegram, before_epoch)),
+        Err(R09ConversionError::OutOfRange {
+            field: "time",
+            value: -1,
+        })
+    );
+}
+
+#[test]
+#[allow(deprecated)]
+fn test_deprecated_conversion_clamps() {
+    let mut telegram = decode(&R16_FRAME, R09Type::R16).expect("cannot decode telegram!");
+    telegram.reporting_point = u32::MAX;
+    telegram.vehicle_number = Some(1 << 31);
+    let meta = crate::telegrams::TelegramMetaInformation {
+        time: chrono::DateTime::from_timestamp_millis(-1)
+            .expect("invalid timestamp!")
+            .naive_utc(),
+        station: uuid::Uuid::nil(),
+        region: 1,
+    };
+
+    let save = R09SaveTelegram::from(telegram.clone(), meta.clone());
+    assert_eq!(save.reporting_point, i32::MAX);
+    assert_eq!(save.vehicle_number, Some(i32::MAX));
+    assert_eq!(save.line, Some(85));
+
+    let grpc = R09GrpcTelegram::create(telegram, meta);
+    assert_eq!(grpc.reporting_point, i32::MAX);
+    assert_eq!(grpc.vehicle_number, Some(i32::MAX));
+    assert_eq!(grpc.time, 0);
+}
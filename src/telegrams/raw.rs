@@ -14,7 +14,7 @@ use struct_field_names_as_array::FieldNamesAsArray;
 use uuid::Uuid;
 
 /// Struct for raw bytes of a VDV420 telegram
-#[derive(Deserialize, Serialize, Debug, Clone)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
 pub struct RawTelegram {
     /// Telegram type, see [`TelegramType`]
     pub telegram_type: TelegramType,
@@ -0,0 +1,170 @@
+use super::family::{CTelegram, FamilyDecodeError, FamilyEncodeError, RTelegram};
+use super::raw::RawTelegram;
+use super::*;
+
+#[test]
+fn test_telegram_type_family() {
+    assert!(TelegramType::R15.is_r());
+    assert!(TelegramType::C00.is_c());
+    assert_eq!(TelegramType::R09.mode(), 9);
+    assert_eq!(TelegramType::C03.mode(), 3);
+}
+
+#[test]
+fn test_decode_family_telegrams() {
+    let raw = RawTelegram {
+        telegram_type: TelegramType::R02,
+        data: vec![0x23, 0x12, 0x34, 0x56],
+    };
+    let telegram = RTelegram::try_from(&raw).expect("cannot decode R telegram!");
+    assert_eq!(telegram.payload, vec![0x12, 0x34, 0x56]);
+    assert_eq!(telegram.nibbles(), vec![1, 2, 3, 4, 5, 6]);
+    assert_eq!(telegram.get_type(), TelegramType::R02);
+    assert_eq!(telegram.encode(), raw.data);
+    assert_eq!(telegram.get_mode(), 2);
+    assert_eq!(telegram.get_length(), 3);
+    assert_eq!(
+        telegram.get_reporting_point().map(|rp| rp.value()),
+        Some(0x3456)
+    );
+    assert_eq!(telegram.get_line(), None);
+
+    assert_eq!(
+        CTelegram::try_from(&raw),
+        Err(FamilyDecodeError::WrongFamily(TelegramType::R02))
+    );
+
+    let raw = RawTelegram {
+        telegram_type: TelegramType::C05,
+        data: vec![0x52, 0xab],
+    };
+    assert_eq!(
+        CTelegram::try_from(&raw),
+        Err(FamilyDecodeError::Truncated {
+            expected: 3,
+            actual: 2
+        })
+    );
+
+    let raw = RawTelegram {
+        telegram_type: TelegramType::C04,
+        data: vec![0x51, 0xab],
+    };
+    assert_eq!(
+        CTelegram::try_from(&raw),
+        Err(FamilyDecodeError::ModeMismatch {
+            expected: 4,
+            found: 5
+        })
+    );
+}
+
+#[test]
+fn test_family_common_fields() {
+    let raw = RawTelegram {
+        telegram_type: TelegramType::C07,
+        data: vec![0x75, 0x00, 0x1a, 0x2b, 0x60, 0x85],
+    };
+    let telegram = CTelegram::try_from(&raw).expect("cannot decode C telegram!");
+    assert_eq!(telegram.get_mode(), 7);
+    assert_eq!(telegram.get_length(), 5);
+    assert_eq!(
+        telegram.get_reporting_point().map(|rp| rp.value()),
+        Some(0x1a2b)
+    );
+    assert_eq!(telegram.get_line(), Some(85));
+
+    let telegram = RTelegram {
+        telegram_type: TelegramType::R01,
+        payload: vec![0x00, 0x1a, 0x2b, 0x60, 0xa5],
+    };
+    assert_eq!(telegram.get_line(), None);
+}
+
+#[test]
+fn test_encode_family_telegrams() {
+    let telegram = CTelegram {
+        telegram_type: TelegramType::C03,
+        payload: vec![0xab; 15],
+    };
+    let data = telegram.try_encode().expect("cannot encode C telegram!");
+    assert_eq!(data[0], 0x3f);
+    assert_eq!(data, telegram.encode());
+
+    // the length nibble can't announce 16 bytes
+    let telegram = RTelegram {
+        telegram_type: TelegramType::R02,
+        payload: vec![0xab; 16],
+    };
+    assert_eq!(
+        telegram.try_encode(),
+        Err(FamilyEncodeError::PayloadTooLong(16))
+    );
+    assert_eq!(telegram.encode()[0], 0x20);
+}
+
+#[test]
+fn test_receive_telegram_serialization() {
+    let raw = RawTelegram {
+        telegram_type: TelegramType::R09,
+        data: vec![
+            0x9a, 0xda, 0x1a, 0x2b, 0x60, 0x85, 0x12, 0x30, 0x40, 0x22, 0x17,
+        ],
+    };
+    let telegram = Telegram::decode(raw);
+    assert!(matches!(telegram, Telegram::R09(_)));
+
+    let receive = ReceiveTelegram {
+        auth: AuthenticationMeta {
+            station: Uuid::nil(),
+            token: "token".to_string(),
+            time: NaiveDateTime::default(),
+        },
+        data: telegram.clone(),
+    };
+    let json = serde_json::to_value(&receive).expect("cannot serialize telegram!");
+    assert_eq!(json["kind"], "r09");
+    assert_eq!(json["token"], "token");
+    assert_eq!(json["line"], 85);
+
+    let deserialized: ReceiveTelegram =
+        serde_json::from_value(json).expect("cannot deserialize telegram!");
+    assert_eq!(deserialized.data, telegram);
+}
+
+#[test]
+fn test_telegram_into_save() {
+    let meta = TelegramMetaInformation {
+        time: NaiveDateTime::default(),
+        station: Uuid::nil(),
+        region: 1,
+    };
+
+    let raw = RawTelegram {
+        telegram_type: TelegramType::C01,
+        data: vec![0x11, 0xff],
+    };
+    let telegram = Telegram::decode(raw.clone());
+    assert_eq!(telegram.get_type(), TelegramType::C01);
+
+    match telegram
+        .into_save(meta.clone())
+        .expect("cannot convert telegram!")
+    {
+        SaveTelegram::Raw(save) => {
+            assert_eq!(save.telegram_type, TelegramType::C01 as i64);
+            assert_eq!(save.data, raw.data);
+        }
+        SaveTelegram::R09(_) => panic!("expected raw telegram!"),
+    }
+
+    // the header announces two bytes of payload but only one was received
+    let truncated = RawTelegram {
+        telegram_type: TelegramType::R03,
+        data: vec![0x32, 0x00],
+    };
+    assert_eq!(
+        Telegram::decode(truncated.clone()),
+        Telegram::Raw(truncated)
+    );
+}
@@ -0,0 +1,157 @@
+//!
+//! The database structs use `NaiveDateTime` in UTC, the gRPC messages unix time stamps in
+//! milliseconds. [`Timestamp`][crate::timestamp::Timestamp] converts between them without guessing
+//! the unit or the time zone.
+//!
+
+#[cfg(test)]
+mod tests;
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use std::fmt;
+
+/// Point in time in UTC with millisecond precision. (De)serialized as unix time stamp in
+/// milliseconds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Timestamp(DateTime<Utc>);
+
+/// Error returned when a time lies outside of the range a [`Timestamp`] or the target type can
+/// represent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimestampRangeError;
+
+impl fmt::Display for TimestampRangeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "timestamp out of range")
+    }
+}
+
+impl std::error::Error for TimestampRangeError {}
+
+impl Timestamp {
+    /// Current time.
+    pub fn now() -> Self {
+        Self::from(Utc::now())
+    }
+
+    /// Milliseconds since the unix epoch, negative for times before 1970.
+    pub fn as_millis(&self) -> i64 {
+        self.0.timestamp_millis()
+    }
+
+    /// Returns the time as [`NaiveDateTime`] in UTC like it is stored in the database.
+    pub fn naive_utc(&self) -> NaiveDateTime {
+        self.0.naive_utc()
+    }
+}
+
+impl From<DateTime<Utc>> for Timestamp {
+    /// Truncates the time to milliseconds.
+    fn from(time: DateTime<Utc>) -> Self {
+        Timestamp(DateTime::from_timestamp_millis(time.timestamp_millis()).unwrap_or(time))
+    }
+}
+
+impl From<NaiveDateTime> for Timestamp {
+    /// Interprets the time as UTC and truncates it to milliseconds.
+    fn from(time: NaiveDateTime) -> Self {
+        Self::from(time.and_utc())
+    }
+}
+
+impl From<Timestamp> for DateTime<Utc> {
+    fn from(timestamp: Timestamp) -> Self {
+        timestamp.0
+    }
+}
+
+impl From<Timestamp> for NaiveDateTime {
+    fn from(timestamp: Timestamp) -> Self {
+        timestamp.naive_utc()
+    }
+}
+
+impl TryFrom<i64> for Timestamp {
+    type Error = TimestampRangeError;
+
+    /// Converts milliseconds since the unix epoch.
+    fn try_from(millis: i64) -> Result<Self, Self::Error> {
+        DateTime::from_timestamp_millis(millis)
+            .map(Timestamp)
+            .ok_or(TimestampRangeError)
+    }
+}
+
+impl TryFrom<u64> for Timestamp {
+    type Error = TimestampRangeError;
+
+    /// Converts milliseconds since the unix epoch like they are sent in the gRPC messages.
+    fn try_from(millis: u64) -> Result<Self, Self::Error> {
+        i64::try_from(millis)
+            .map_err(|_| TimestampRangeError)
+            .and_then(Timestamp::try_from)
+    }
+}
+
+impl TryFrom<Timestamp> for u64 {
+    type Error = TimestampRangeError;
+
+    /// Converts to milliseconds since the unix epoch, fails for times before 1970.
+    fn try_from(timestamp: Timestamp) -> Result<Self, Self::Error> {
+        u64::try_from(timestamp.as_millis()).map_err(|_| TimestampRangeError)
+    }
+}
+
+#[cfg(feature = "grpc")]
+impl From<Timestamp> for prost_types::Timestamp {
+    fn from(timestamp: Timestamp) -> Self {
+        prost_types::Timestamp {
+            seconds: timestamp.0.timestamp(),
+            nanos: timestamp.0.timestamp_subsec_nanos() as i32,
+        }
+    }
+}
+
+#[cfg(feature = "grpc")]
+impl TryFrom<prost_types::Timestamp> for Timestamp {
+    type Error = TimestampRangeError;
+
+    /// Normalizes the time stamp and truncates it to milliseconds.
+    fn try_from(mut timestamp: prost_types::Timestamp) -> Result<Self, Self::Error> {
+        timestamp.normalize();
+        DateTime::from_timestamp(timestamp.seconds, timestamp.nanos as u32)
+            .map(Timestamp::from)
+            .ok_or(TimestampRangeError)
+    }
+}
+
+impl Serialize for Timestamp {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_i64(self.as_millis())
+    }
+}
+
+impl<'de> Deserialize<'de> for Timestamp {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let millis = i64::deserialize(deserializer)?;
+        Timestamp::try_from(millis).map_err(serde::de::Error::custom)
+    }
+}
+
+impl fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            self.0.to_rfc3339_opts(chrono::SecondsFormat::Millis, true)
+        )
+    }
+}
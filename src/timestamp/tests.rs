@@ -0,0 +1,57 @@
+use super::*;
+
+#[test]
+fn test_timestamp_conversions() {
+    let time = DateTime::from_timestamp(1_681_000_000, 123_456_789)
+        .unwrap()
+        .naive_utc();
+    let timestamp = Timestamp::from(time);
+
+    // sub millisecond precision is dropped
+    assert_eq!(timestamp.as_millis(), 1_681_000_000_123);
+    assert_eq!(
+        NaiveDateTime::from(timestamp),
+        DateTime::from_timestamp_millis(1_681_000_000_123)
+            .unwrap()
+            .naive_utc()
+    );
+    assert_eq!(Timestamp::try_from(1_681_000_000_123_u64), Ok(timestamp));
+    assert_eq!(u64::try_from(timestamp), Ok(1_681_000_000_123));
+    assert_eq!(
+        DateTime::<Utc>::from(timestamp),
+        time.and_utc() - chrono::Duration::nanoseconds(456_789)
+    );
+    assert_eq!(timestamp.to_string(), "2023-04-09T00:26:40.123Z");
+
+    let before_epoch = Timestamp::try_from(-1_i64).unwrap();
+    assert_eq!(u64::try_from(before_epoch), Err(TimestampRangeError));
+    assert_eq!(Timestamp::try_from(u64::MAX), Err(TimestampRangeError));
+    assert_eq!(Timestamp::try_from(i64::MAX), Err(TimestampRangeError));
+}
+
+#[cfg(feature = "grpc")]
+#[test]
+fn test_timestamp_protobuf() {
+    let timestamp = Timestamp::try_from(1_681_000_000_123_i64).unwrap();
+    let proto = prost_types::Timestamp::from(timestamp);
+    assert_eq!(proto.seconds, 1_681_000_000);
+    assert_eq!(proto.nanos, 123_000_000);
+    assert_eq!(Timestamp::try_from(proto), Ok(timestamp));
+
+    // denormalized time stamps are accepted
+    let proto = prost_types::Timestamp {
+        seconds: 1_681_000_001,
+        nanos: -877_000_000,
+    };
+    assert_eq!(Timestamp::try_from(proto), Ok(timestamp));
+}
+
+#[test]
+fn test_timestamp_serde() {
+    let timestamp = Timestamp::try_from(1_681_000_000_123_i64).unwrap();
+    assert_eq!(serde_json::to_string(&timestamp).unwrap(), "1681000000123");
+    assert_eq!(
+        serde_json::from_str::<Timestamp>("1681000000123").unwrap(),
+        timestamp
+    );
+}
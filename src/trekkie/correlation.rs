@@ -0,0 +1,119 @@
+//!
+//! Correlates the gps points recorded during a [`TrekkieRun`] with the R09 telegrams the vehicle
+//! sent, which results in one raw transmission location per telegram.
+//!
+
+use super::TrekkieRun;
+use crate::locations::InsertTransmissionLocationRaw;
+use crate::locations::gps::GpsPoint;
+use crate::locations::gps::clean::{CleaningConfig, clean};
+use crate::locations::gps::track::GpsTrack;
+use crate::telegrams::r09::R09SaveTelegram;
+
+use serde::{Deserialize, Serialize};
+
+/// Maximum time in milliseconds between two gps points the position of a telegram is
+/// interpolated between, used by [`CorrelationConfig::default`].
+pub const DEFAULT_MAX_TIME_GAP: i64 = 30_000;
+
+/// Configures how gps points and telegrams are correlated.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CorrelationConfig {
+    /// maximum time in milliseconds between the gps points before and after a telegram, telegrams
+    /// inside bigger gaps of the recording are skipped
+    pub max_time_gap: i64,
+    /// cleaning applied to the gps points before they are correlated, [`None`] uses them as they
+    /// are
+    pub cleaning: Option<CleaningConfig>,
+}
+
+impl Default for CorrelationConfig {
+    fn default() -> Self {
+        CorrelationConfig {
+            max_time_gap: DEFAULT_MAX_TIME_GAP,
+            cleaning: None,
+        }
+    }
+}
+
+/// Same as [`correlate_with`] using [`CorrelationConfig::default`].
+pub fn correlate(
+    run: &TrekkieRun,
+    gps: &[GpsPoint],
+    telegrams: &[R09SaveTelegram],
+) -> Vec<InsertTransmissionLocationRaw> {
+    correlate_with(run, gps, telegrams, &CorrelationConfig::default())
+}
+
+/// Creates a raw transmission location for every telegram the vehicle of `run` sent while it was
+/// recorded. Telegrams of other regions, lines or runs and gps points of other trekkie runs are
+/// ignored, the remaining gps points are cleaned if configured. The position is interpolated
+/// between the gps points right before and after the telegram by a [`GpsTrack`], telegrams
+/// outside the recording or inside a gap longer than [`CorrelationConfig::max_time_gap`] are
+/// skipped.
+pub fn correlate_with(
+    run: &TrekkieRun,
+    gps: &[GpsPoint],
+    telegrams: &[R09SaveTelegram],
+    config: &CorrelationConfig,
+) -> Vec<InsertTransmissionLocationRaw> {
+    let points: Vec<GpsPoint> = gps
+        .iter()
+        .filter(|point| point.trekkie_run == run.id)
+        .copied()
+        .collect();
+    let points = match &config.cleaning {
+        Some(cleaning) => clean(points, cleaning).0,
+        None => points,
+    };
+    let Ok(track) = GpsTrack::sorted(points) else {
+        return Vec::new();
+    };
+    let track = track
+        .with_max_gap(config.max_time_gap)
+        .with_max_extrapolation(0);
+
+    telegrams
+        .iter()
+        .filter(|telegram| {
+            telegram.region == run.region
+                && telegram.line == Some(run.line)
+                && telegram.run_number == Some(run.run)
+                && (run.start_time..=run.end_time).contains(&telegram.time)
+        })
+        .filter_map(|telegram| {
+            let position = track.position_at(telegram.time).ok()?;
+
+            Some(InsertTransmissionLocationRaw {
+                id: None,
+                region: run.region,
+                reporting_point: telegram.reporting_point,
+                lat: position.lat,
+                lon: position.lon,
+                trekkie_run: run.id,
+                run_owner: run.owner,
+                accuracy: position.accuracy,
+                time_offset: Some(position.time_offset),
+            })
+        })
+        .collect()
+}
+
+impl TrekkieRun {
+    /// Correlates the run with [`correlate_with`] and marks it as correlated if `gps` contains
+    /// points of the run. Runs without gps points stay uncorrelated so they are picked up again
+    /// once their points arrive.
+    pub fn correlate(
+        &mut self,
+        gps: &[GpsPoint],
+        telegrams: &[R09SaveTelegram],
+        config: &CorrelationConfig,
+    ) -> Vec<InsertTransmissionLocationRaw> {
+        let locations = correlate_with(self, gps, telegrams, config);
+        if gps.iter().any(|point| point.trekkie_run == self.id) {
+            self.correlated = true;
+        }
+        locations
+    }
+}
@@ -0,0 +1,143 @@
+//!
+//! Reads and writes the gps points of trekkie runs as
+//! [GeoJSON](https://datatracker.ietf.org/doc/html/rfc7946). Runs are written as a
+//! `FeatureCollection` with a `Point` feature per gps point carrying its time, speed, bearing and
+//! accuracy in the properties. Besides those, `LineString` features with the times in
+//! `coordinateProperties.times` or `coordTimes` (as written by togeojson) can be read.
+//!
+
+use super::{TrackImportError, TrekkieRun, format_time, parse_time};
+use crate::locations::gps::{GpsConversionError, GpsPoint, InsertGpsPoint};
+
+use serde_json::{Value, json};
+
+/// Reads all gps points of a GeoJSON `FeatureCollection`, `Feature` or geometry as gps points of
+/// `run`. Every point needs a time, features with other geometries are ignored.
+pub fn read(input: &str, run: &TrekkieRun) -> Result<Vec<InsertGpsPoint>, TrackImportError> {
+    let value: Value =
+        serde_json::from_str(input).map_err(|e| TrackImportError::Json(e.to_string()))?;
+
+    let mut points = Vec::new();
+    match value["type"].as_str() {
+        Some("FeatureCollection") => {
+            let features = value["features"]
+                .as_array()
+                .ok_or(TrackImportError::Missing("features"))?;
+            for feature in features {
+                read_feature(feature, run, &mut points)?;
+            }
+        }
+        Some("Feature") => read_feature(&value, run, &mut points)?,
+        Some(_) => read_geometry(&value, &Value::Null, run, &mut points)?,
+        None => return Err(TrackImportError::Missing("type")),
+    }
+
+    Ok(points)
+}
+
+fn read_feature(
+    feature: &Value,
+    run: &TrekkieRun,
+    points: &mut Vec<InsertGpsPoint>,
+) -> Result<(), TrackImportError> {
+    read_geometry(&feature["geometry"], &feature["properties"], run, points)
+}
+
+fn read_geometry(
+    geometry: &Value,
+    properties: &Value,
+    run: &TrekkieRun,
+    points: &mut Vec<InsertGpsPoint>,
+) -> Result<(), TrackImportError> {
+    match geometry["type"].as_str() {
+        Some("Point") => {
+            let time = properties["time"]
+                .as_str()
+                .ok_or(TrackImportError::Missing("time"))?;
+            let mut point = read_position(&geometry["coordinates"], time, run)?;
+            point.speed = properties["speed"].as_f64();
+            point.bearing = properties["bearing"].as_f64();
+            point.accuracy = properties["accuracy"].as_f64();
+            point.vertical_accuracy = properties["vertical_accuracy"].as_f64();
+            points.push(point);
+        }
+        Some("LineString") => {
+            let coordinates = geometry["coordinates"]
+                .as_array()
+                .ok_or(TrackImportError::Missing("coordinates"))?;
+            let times = properties["coordinateProperties"]["times"]
+                .as_array()
+                .or_else(|| properties["coordTimes"].as_array())
+                .ok_or(TrackImportError::Missing("times"))?;
+            if times.len() != coordinates.len() {
+                return Err(TrackImportError::InvalidValue {
+                    field: "times",
+                    value: format!("{} times for {} positions", times.len(), coordinates.len()),
+                });
+            }
+
+            for (position, time) in coordinates.iter().zip(times) {
+                let time = time.as_str().ok_or(TrackImportError::Missing("time"))?;
+                points.push(read_position(position, time, run)?);
+            }
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+/// Reads a `[longitude, latitude, elevation]` position, the elevation is optional.
+fn read_position(
+    position: &Value,
+    time: &str,
+    run: &TrekkieRun,
+) -> Result<InsertGpsPoint, TrackImportError> {
+    let coordinate = |index: usize, field: &'static str| {
+        position[index]
+            .as_f64()
+            .ok_or(TrackImportError::Missing(field))
+    };
+
+    run.import_point(
+        parse_time("time", time)?,
+        (coordinate(1, "latitude")?, coordinate(0, "longitude")?),
+        position[2].as_f64(),
+    )
+}
+
+/// Writes the gps points of `run` as a `FeatureCollection` of `Point` features. Fails with
+/// [`GpsConversionError::RunMismatch`] if a point was recorded during a different run.
+pub fn write(run: &TrekkieRun, points: &[GpsPoint]) -> Result<Value, GpsConversionError> {
+    run.check_points(points)?;
+
+    let features: Vec<Value> = points
+        .iter()
+        .map(|point| {
+            let mut coordinates = vec![point.lon, point.lat];
+            coordinates.extend(point.elevation);
+
+            json!({
+                "type": "Feature",
+                "id": point.id,
+                "geometry": {
+                    "type": "Point",
+                    "coordinates": coordinates,
+                },
+                "properties": {
+                    "trekkie_run": run.id,
+                    "time": format_time(point.timestamp),
+                    "speed": point.speed,
+                    "bearing": point.bearing,
+                    "accuracy": point.accuracy,
+                    "vertical_accuracy": point.vertical_accuracy,
+                },
+            })
+        })
+        .collect();
+
+    Ok(json!({
+        "type": "FeatureCollection",
+        "features": features,
+    }))
+}
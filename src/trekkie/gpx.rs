@@ -0,0 +1,145 @@
+//!
+//! Reads and writes the gps points of trekkie runs as
+//! [GPX 1.1](https://www.topografix.com/GPX/1/1/). Speed, bearing and accuracy don't exist in GPX,
+//! they are written as extensions in the [`NAMESPACE`] of tlms. When reading, the `speed` and
+//! `course` of the widely used Garmin `TrackPointExtension` are understood as well.
+//!
+
+use super::{TrackImportError, TrekkieRun, format_time, parse_time};
+use crate::locations::gps::{GpsConversionError, GpsPoint, InsertGpsPoint};
+
+use roxmltree::{Document, Node};
+
+use std::fmt::Write;
+
+/// Namespace of the GPX extensions holding speed, bearing and accuracy.
+pub const NAMESPACE: &str = "https://github.com/tlm-solutions/tlms.rs/gpx/1";
+
+/// Reads all track points of a GPX file as gps points of `run`. Every track point needs a time,
+/// routes and waypoints are ignored.
+pub fn read(input: &str, run: &TrekkieRun) -> Result<Vec<InsertGpsPoint>, TrackImportError> {
+    let document = Document::parse(input).map_err(TrackImportError::Xml)?;
+
+    document
+        .descendants()
+        .filter(|node| node.has_tag_name("trkpt"))
+        .map(|node| read_point(node, run))
+        .collect()
+}
+
+/// Reads a single `trkpt` element.
+fn read_point(node: Node, run: &TrekkieRun) -> Result<InsertGpsPoint, TrackImportError> {
+    let attribute = |name: &'static str| {
+        let value = node
+            .attribute(name)
+            .ok_or(TrackImportError::Missing(name))?;
+        parse_number(name, value)
+    };
+    let time = child_text(node, "time").ok_or(TrackImportError::Missing("time"))?;
+    let elevation = child_number(node, "ele")?;
+
+    let mut point = run.import_point(
+        parse_time("time", time)?,
+        (attribute("lat")?, attribute("lon")?),
+        elevation,
+    )?;
+    point.speed = child_number(node, "speed")?;
+    point.bearing = match child_number(node, "bearing")? {
+        Some(bearing) => Some(bearing),
+        None => child_number(node, "course")?,
+    };
+    point.accuracy = child_number(node, "accuracy")?;
+    point.vertical_accuracy = child_number(node, "vertical_accuracy")?;
+
+    Ok(point)
+}
+
+/// Text of the first descendant called `name`, regardless of its namespace.
+fn child_text<'a>(node: Node<'a, '_>, name: &str) -> Option<&'a str> {
+    node.descendants()
+        .find(|child| child.tag_name().name() == name)
+        .and_then(|child| child.text())
+}
+
+/// Number inside the first descendant called `name`, if there is one.
+fn child_number(node: Node, name: &'static str) -> Result<Option<f64>, TrackImportError> {
+    child_text(node, name)
+        .map(|value| parse_number(name, value))
+        .transpose()
+}
+
+fn parse_number(field: &'static str, value: &str) -> Result<f64, TrackImportError> {
+    value
+        .trim()
+        .parse()
+        .map_err(|_| TrackImportError::InvalidValue {
+            field,
+            value: value.to_string(),
+        })
+}
+
+/// Writes the gps points of `run` as a GPX file with a single track. Fails with
+/// [`GpsConversionError::RunMismatch`] if a point was recorded during a different run.
+pub fn write(run: &TrekkieRun, points: &[GpsPoint]) -> Result<String, GpsConversionError> {
+    run.check_points(points)?;
+
+    let mut gpx = String::new();
+    // writing into a string never fails
+    let _ = write_gpx(&mut gpx, run, points);
+    Ok(gpx)
+}
+
+fn write_gpx(gpx: &mut String, run: &TrekkieRun, points: &[GpsPoint]) -> std::fmt::Result {
+    writeln!(gpx, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(
+        gpx,
+        r#"<gpx version="1.1" creator="{}" xmlns="http://www.topografix.com/GPX/1/1" xmlns:tlms="{NAMESPACE}">"#,
+        escape(&run.app_name)
+    )?;
+    writeln!(gpx, "  <metadata>")?;
+    writeln!(gpx, "    <time>{}</time>", format_time(run.start_time))?;
+    writeln!(gpx, "  </metadata>")?;
+    writeln!(gpx, "  <trk>")?;
+    writeln!(gpx, "    <name>line {} run {}</name>", run.line, run.run)?;
+    writeln!(gpx, "    <trkseg>")?;
+    for point in points {
+        writeln!(
+            gpx,
+            r#"      <trkpt lat="{}" lon="{}">"#,
+            point.lat, point.lon
+        )?;
+        if let Some(elevation) = point.elevation {
+            writeln!(gpx, "        <ele>{elevation}</ele>")?;
+        }
+        writeln!(gpx, "        <time>{}</time>", format_time(point.timestamp))?;
+
+        let extensions = [
+            ("speed", point.speed),
+            ("bearing", point.bearing),
+            ("accuracy", point.accuracy),
+            ("vertical_accuracy", point.vertical_accuracy),
+        ];
+        if extensions.iter().any(|(_, value)| value.is_some()) {
+            writeln!(gpx, "        <extensions>")?;
+            for (name, value) in extensions {
+                if let Some(value) = value {
+                    writeln!(gpx, "          <tlms:{name}>{value}</tlms:{name}>")?;
+                }
+            }
+            writeln!(gpx, "        </extensions>")?;
+        }
+        writeln!(gpx, "      </trkpt>")?;
+    }
+    writeln!(gpx, "    </trkseg>")?;
+    writeln!(gpx, "  </trk>")?;
+    writeln!(gpx, "</gpx>")
+}
+
+/// Escapes the characters with a special meaning in XML.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
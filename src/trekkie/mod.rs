@@ -1,3 +1,19 @@
+#[cfg(feature = "locations")]
+pub mod correlation;
+#[cfg(feature = "locations")]
+pub mod geojson;
+#[cfg(feature = "locations")]
+pub mod gpx;
+#[cfg(all(test, feature = "locations"))]
+mod tests;
+
+#[cfg(feature = "locations")]
+use crate::grpc::{GpsValidationError, validate_position};
+#[cfg(feature = "locations")]
+use crate::locations::gps::{GpsConversionError, GpsPoint, InsertGpsPoint};
+#[cfg(feature = "locations")]
+pub use correlation::{CorrelationConfig, correlate, correlate_with};
+
 use chrono::NaiveDateTime;
 use diesel::{Insertable, Queryable};
 use serde::{Deserialize, Serialize};
@@ -6,6 +22,9 @@ use uuid::Uuid;
 
 use crate::schema::trekkie_runs;
 
+#[cfg(feature = "locations")]
+use std::fmt;
+
 /// This saves a measurement of a vehicle for later correlation. This struct tells when the vehicle
 /// was measured and what are line and run number. The gps_table references trekkie_runs for all
 /// the saved gps locations
@@ -37,3 +56,97 @@ pub struct TrekkieRun {
     /// name of the applicatation that submitted this data
     pub app_name: String,
 }
+
+/// Error returned when gps points recorded by another app can't be imported.
+#[cfg(feature = "locations")]
+#[derive(Debug, Clone, PartialEq)]
+pub enum TrackImportError {
+    /// The GPX file is not valid XML.
+    Xml(roxmltree::Error),
+    /// The GeoJSON file is not valid JSON, contains the error message.
+    Json(String),
+    /// A required element, attribute or member is missing.
+    Missing(&'static str),
+    /// A value can't be parsed.
+    InvalidValue {
+        /// name of the element, attribute or member
+        field: &'static str,
+        /// the value that couldn't be parsed
+        value: String,
+    },
+    /// Latitude or longitude are not a position on earth.
+    InvalidPosition(GpsValidationError),
+}
+
+#[cfg(feature = "locations")]
+impl fmt::Display for TrackImportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TrackImportError::Xml(e) => write!(f, "invalid xml: {e}"),
+            TrackImportError::Json(e) => write!(f, "invalid json: {e}"),
+            TrackImportError::Missing(field) => write!(f, "missing {field}"),
+            TrackImportError::InvalidValue { field, value } => {
+                write!(f, "invalid {field}: {value}")
+            }
+            TrackImportError::InvalidPosition(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+#[cfg(feature = "locations")]
+impl std::error::Error for TrackImportError {}
+
+#[cfg(feature = "locations")]
+impl TrekkieRun {
+    /// Creates a gps point of this run after checking its position.
+    fn import_point(
+        &self,
+        timestamp: NaiveDateTime,
+        (lat, lon): (f64, f64),
+        elevation: Option<f64>,
+    ) -> Result<InsertGpsPoint, TrackImportError> {
+        validate_position(lat, lon).map_err(TrackImportError::InvalidPosition)?;
+
+        Ok(InsertGpsPoint {
+            id: None,
+            trekkie_run: self.id,
+            timestamp,
+            lat,
+            lon,
+            elevation,
+            accuracy: None,
+            vertical_accuracy: None,
+            bearing: None,
+            speed: None,
+        })
+    }
+
+    /// Checks that all gps points were recorded during this run.
+    fn check_points(&self, points: &[GpsPoint]) -> Result<(), GpsConversionError> {
+        match points.iter().find(|point| point.trekkie_run != self.id) {
+            Some(point) => Err(GpsConversionError::RunMismatch {
+                point: point.trekkie_run,
+                run: self.id,
+            }),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Parses a RFC 3339 time stamp like `2023-04-09T00:26:40.123Z`.
+#[cfg(feature = "locations")]
+fn parse_time(field: &'static str, value: &str) -> Result<NaiveDateTime, TrackImportError> {
+    chrono::DateTime::parse_from_rfc3339(value.trim())
+        .map(|time| time.naive_utc())
+        .map_err(|_| TrackImportError::InvalidValue {
+            field,
+            value: value.to_string(),
+        })
+}
+
+/// Formats a time stamp like `2023-04-09T00:26:40.123Z`.
+#[cfg(feature = "locations")]
+fn format_time(time: NaiveDateTime) -> String {
+    time.and_utc()
+        .to_rfc3339_opts(chrono::SecondsFormat::Millis, true)
+}
@@ -0,0 +1,325 @@
+use super::*;
+use crate::locations::gps::clean::CleaningConfig;
+use crate::locations::gps::tests::{gps_point, time};
+use crate::locations::gps::{GpsConversionError, GpsPoint};
+use crate::telegrams::r09::{R09SaveTelegram, R09Type};
+
+fn trekkie_run() -> TrekkieRun {
+    TrekkieRun {
+        start_time: time(0),
+        end_time: time(600_000),
+        line: 85,
+        run: 12,
+        region: 1,
+        owner: Uuid::from_u128(7),
+        finished: true,
+        id: Uuid::nil(),
+        correlated: false,
+        app_commit: String::from("0000000"),
+        app_name: String::from("stasi"),
+    }
+}
+
+fn telegram(millis: i64, reporting_point: i32) -> R09SaveTelegram {
+    R09SaveTelegram {
+        id: None,
+        time: time(millis),
+        station: Uuid::nil(),
+        r09_type: R09Type::R16,
+        delay: None,
+        reporting_point,
+        junction: 0,
+        direction: 0,
+        request_status: 0,
+        priority: None,
+        direction_request: None,
+        line: Some(85),
+        run_number: Some(12),
+        destination_number: None,
+        train_length: None,
+        vehicle_number: None,
+        operator: None,
+        region: 1,
+    }
+}
+
+#[test]
+fn test_correlate() {
+    let run = trekkie_run();
+    let other_run = GpsPoint {
+        trekkie_run: Uuid::from_u128(2),
+        ..gps_point(15_000, 0.0, 0.0)
+    };
+    // unsorted on purpose
+    let gps = [
+        GpsPoint {
+            accuracy: Some(8.0),
+            ..gps_point(20_000, 51.002, 13.004)
+        },
+        gps_point(10_000, 51.0, 13.0),
+        other_run,
+        GpsPoint {
+            accuracy: None,
+            ..gps_point(30_000, 51.003, 13.006)
+        },
+        gps_point(100_000, 51.1, 13.1),
+    ];
+
+    let mut other_line = telegram(12_000, 6);
+    other_line.line = Some(3);
+    let mut other_region = telegram(12_000, 7);
+    other_region.region = 2;
+    let telegrams = [
+        telegram(12_500, 1),
+        telegram(20_000, 2),
+        telegram(25_000, 3),
+        // gap of 70 seconds in the recording
+        telegram(50_000, 4),
+        // after the last gps point
+        telegram(110_000, 5),
+        other_line,
+        other_region,
+        // before the run started
+        telegram(-1_000, 8),
+    ];
+
+    let locations = correlate(&run, &gps, &telegrams);
+    let reporting_points: Vec<i32> = locations
+        .iter()
+        .map(|location| location.reporting_point)
+        .collect();
+    assert_eq!(reporting_points, vec![1, 2, 3]);
+    assert!(locations.iter().all(|location| location.region == 1
+        && location.trekkie_run == run.id
+        && location.run_owner == run.owner
+        && location.id.is_none()));
+
+    // a quarter of the way from the first to the second point
+    assert!((locations[0].lat - 51.0005).abs() < 1e-9);
+    assert!((locations[0].lon - 13.001).abs() < 1e-9);
+    assert_eq!(locations[0].accuracy, Some(8.0));
+    assert_eq!(locations[0].time_offset, Some(2_500));
+
+    assert_eq!((locations[1].lat, locations[1].lon), (51.002, 13.004));
+    assert_eq!(locations[1].accuracy, Some(8.0));
+    assert_eq!(locations[1].time_offset, Some(0));
+
+    assert_eq!(locations[2].accuracy, None);
+    assert_eq!(locations[2].time_offset, Some(5_000));
+
+    // a bigger gap lets the telegram inside it through
+    let config = CorrelationConfig {
+        max_time_gap: 70_000,
+        ..CorrelationConfig::default()
+    };
+    assert_eq!(correlate_with(&run, &gps, &telegrams, &config).len(), 4);
+
+    // without the inaccurate point the first telegram is interpolated towards the next one
+    let config = CorrelationConfig {
+        cleaning: Some(CleaningConfig {
+            max_accuracy: 6.0,
+            ..CleaningConfig::default()
+        }),
+        ..CorrelationConfig::default()
+    };
+    let locations = correlate_with(&run, &gps, &telegrams, &config);
+    assert_eq!(locations.len(), 3);
+    assert!((locations[0].lat - 51.000375).abs() < 1e-9);
+    assert_eq!(locations[0].accuracy, None);
+    assert_eq!(locations[0].time_offset, Some(2_500));
+}
+
+#[test]
+fn test_trekkie_run_correlate() {
+    let mut run = trekkie_run();
+    let gps = [gps_point(0, 51.0, 13.0), gps_point(1_000, 51.0, 13.0)];
+
+    let locations = run.correlate(&gps, &[telegram(500, 1)], &CorrelationConfig::default());
+    assert_eq!(locations.len(), 1);
+    assert!(run.correlated);
+
+    // runs without telegrams are correlated as well
+    let mut run = trekkie_run();
+    assert!(
+        run.correlate(&gps, &[], &CorrelationConfig::default())
+            .is_empty()
+    );
+    assert!(run.correlated);
+
+    // runs without gps points have nothing to correlate
+    let mut run = trekkie_run();
+    assert!(
+        run.correlate(&[], &[telegram(500, 1)], &CorrelationConfig::default())
+            .is_empty()
+    );
+    assert!(!run.correlated);
+
+    let mut other = gps_point(0, 51.0, 13.0);
+    other.trekkie_run = Uuid::from_u128(2);
+    run.correlate(&[other], &[telegram(500, 1)], &CorrelationConfig::default());
+    assert!(!run.correlated);
+}
+
+/// Gps points with and without the optional values.
+fn recorded_points() -> Vec<GpsPoint> {
+    let mut moving = gps_point(1_500, 51.0512345678, 13.7312345678);
+    moving.accuracy = Some(3.5);
+    moving.elevation = Some(112.25);
+    moving.vertical_accuracy = Some(6.0);
+    moving.speed = Some(8.5);
+    moving.bearing = Some(271.0);
+
+    let standing = GpsPoint {
+        accuracy: None,
+        ..gps_point(0, 51.05, 13.73)
+    };
+
+    vec![standing, moving]
+}
+
+/// Checks that the imported points match the recorded ones.
+fn assert_imported(imported: &[crate::locations::gps::InsertGpsPoint], recorded: &[GpsPoint]) {
+    assert_eq!(imported.len(), recorded.len());
+    for (imported, recorded) in imported.iter().zip(recorded) {
+        assert_eq!(imported.id, None);
+        assert_eq!(imported.trekkie_run, trekkie_run().id);
+        assert_eq!(imported.timestamp, recorded.timestamp);
+        assert_eq!((imported.lat, imported.lon), (recorded.lat, recorded.lon));
+        assert_eq!(imported.elevation, recorded.elevation);
+        assert_eq!(imported.accuracy, recorded.accuracy);
+        assert_eq!(imported.vertical_accuracy, recorded.vertical_accuracy);
+        assert_eq!(imported.speed, recorded.speed);
+        assert_eq!(imported.bearing, recorded.bearing);
+    }
+}
+
+#[test]
+fn test_gpx_round_trip() {
+    let mut run = trekkie_run();
+    run.app_name = String::from("<stasi & co>");
+    let points = recorded_points();
+
+    let gpx = gpx::write(&run, &points).expect("cannot write gpx!");
+    assert!(gpx.contains(r#"creator="&lt;stasi &amp; co&gt;""#));
+    assert!(gpx.contains("<time>2023-04-09T00:26:41.500Z</time>"));
+    assert!(gpx.contains("<tlms:speed>8.5</tlms:speed>"));
+
+    assert_imported(&gpx::read(&gpx, &run).unwrap(), &points);
+
+    // points of other runs are rejected
+    run.id = Uuid::from_u128(2);
+    assert_eq!(
+        gpx::write(&run, &points),
+        Err(GpsConversionError::RunMismatch {
+            point: trekkie_run().id,
+            run: run.id,
+        })
+    );
+}
+
+#[test]
+fn test_gpx_read() {
+    // exported by a garmin device
+    let gpx = r#"<?xml version="1.0" encoding="UTF-8"?>
+<gpx version="1.1" creator="Garmin" xmlns="http://www.topografix.com/GPX/1/1"
+     xmlns:gpxtpx="http://www.garmin.com/xmlschemas/TrackPointExtension/v2">
+  <wpt lat="51.1" lon="13.8"><name>ignored</name></wpt>
+  <trk><trkseg>
+    <trkpt lat="51.05" lon="13.73">
+      <ele>110.5</ele>
+      <time>2023-04-09T02:26:40+02:00</time>
+      <extensions><gpxtpx:TrackPointExtension>
+        <gpxtpx:speed>4.2</gpxtpx:speed>
+        <gpxtpx:course>90</gpxtpx:course>
+      </gpxtpx:TrackPointExtension></extensions>
+    </trkpt>
+  </trkseg></trk>
+</gpx>"#;
+
+    let points = gpx::read(gpx, &trekkie_run()).unwrap();
+    assert_eq!(points.len(), 1);
+    assert_eq!(points[0].timestamp, time(0));
+    assert_eq!(points[0].elevation, Some(110.5));
+    assert_eq!(points[0].speed, Some(4.2));
+    assert_eq!(points[0].bearing, Some(90.0));
+    assert_eq!(points[0].accuracy, None);
+
+    let missing_time = r#"<gpx><trk><trkseg><trkpt lat="51" lon="13"/></trkseg></trk></gpx>"#;
+    assert_eq!(
+        gpx::read(missing_time, &trekkie_run()).err(),
+        Some(TrackImportError::Missing("time"))
+    );
+    let invalid_lat =
+        r#"<gpx><trkpt lat="91" lon="13"><time>2023-04-09T00:26:40Z</time></trkpt></gpx>"#;
+    assert!(matches!(
+        gpx::read(invalid_lat, &trekkie_run()),
+        Err(TrackImportError::InvalidPosition(_))
+    ));
+    assert!(matches!(
+        gpx::read("<gpx>", &trekkie_run()),
+        Err(TrackImportError::Xml(_))
+    ));
+}
+
+#[test]
+fn test_geojson_round_trip() {
+    let mut run = trekkie_run();
+    let points = recorded_points();
+
+    let geojson = geojson::write(&run, &points).expect("cannot write geojson!");
+    assert_eq!(geojson["type"], "FeatureCollection");
+    let feature = &geojson["features"][1];
+    assert_eq!(
+        feature["geometry"]["coordinates"],
+        serde_json::json!([13.7312345678, 51.0512345678, 112.25])
+    );
+    assert_eq!(feature["properties"]["time"], "2023-04-09T00:26:41.500Z");
+    assert_eq!(feature["properties"]["bearing"], 271.0);
+
+    let imported = geojson::read(&geojson.to_string(), &run).unwrap();
+    assert_imported(&imported, &points);
+
+    // points of other runs are rejected
+    run.id = Uuid::from_u128(2);
+    assert_eq!(
+        geojson::write(&run, &points),
+        Err(GpsConversionError::RunMismatch {
+            point: trekkie_run().id,
+            run: run.id,
+        })
+    );
+}
+
+#[test]
+fn test_geojson_read_line_string() {
+    let line = r#"{
+        "type": "Feature",
+        "geometry": {"type": "LineString", "coordinates": [[13.73, 51.05, 100], [13.74, 51.06]]},
+        "properties": {
+            "coordinateProperties": {"times": ["2023-04-09T00:26:40Z", "2023-04-09T00:26:41Z"]}
+        }
+    }"#;
+    let points = geojson::read(line, &trekkie_run()).unwrap();
+    assert_eq!(points.len(), 2);
+    assert_eq!((points[1].lat, points[1].lon), (51.06, 13.74));
+    assert_eq!(points[0].elevation, Some(100.0));
+    assert_eq!(points[1].elevation, None);
+    assert_eq!(points[1].timestamp, time(1_000));
+
+    let missing_time = r#"{"type": "Point", "coordinates": [13.73, 51.05]}"#;
+    assert_eq!(
+        geojson::read(missing_time, &trekkie_run()).err(),
+        Some(TrackImportError::Missing("time"))
+    );
+    let too_few_times = r#"{"type": "Feature",
+        "geometry": {"type": "LineString", "coordinates": [[13.73, 51.05], [13.74, 51.06]]},
+        "properties": {"coordTimes": ["2023-04-09T00:26:40Z"]}}"#;
+    assert!(matches!(
+        geojson::read(too_few_times, &trekkie_run()),
+        Err(TrackImportError::InvalidValue { field: "times", .. })
+    ));
+    assert!(matches!(
+        geojson::read("{", &trekkie_run()),
+        Err(TrackImportError::Json(_))
+    ));
+}